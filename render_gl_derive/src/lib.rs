@@ -0,0 +1,52 @@
+//! Derive macros for `render_gl`, re-exported from there behind the
+//! `derive` feature
+
+mod uniforms;
+mod vertex;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Implements `render_gl::Uniforms` for a struct with named fields, setting
+/// each field as the uniform of the same name.
+///
+/// Field attributes:
+/// - `#[uniform(name = "uColor")]` sets the uniform under another name
+/// - `#[uniform(sampler = 2)]` attaches the field (anything with an
+///   `attach_to_unit` method, e.g. a `Texture` or a reference to one) to
+///   texture unit 2 and points the sampler uniform at that unit
+/// - `#[uniform(skip)]` leaves the field out
+///
+/// The struct attribute `#[uniform(prefix = "material.")]` is put in front of
+/// every name, for uniforms that are members of a GLSL struct
+#[proc_macro_derive(Uniforms, attributes(uniform))]
+pub fn derive_uniforms(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    uniforms::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `render_gl::buffer::Vertex` for a `#[repr(C)]` struct with
+/// named fields, making each field an attribute. Field types have to
+/// implement `render_gl::buffer::VertexAttribType`. It also implements
+/// `render_gl::buffer::Pod`, so every field, including skipped ones, has to
+/// be plain old data.
+///
+/// Field attributes:
+/// - `#[vertex(location = 3)]` sets the attribute location. Fields without
+///   one take the location after the previous field's, which is several
+///   locations further on for matrices
+/// - `#[vertex(normalized)]` maps integer data to `[0, 1]`/`[-1, 1]` floats,
+///   including `i32`/`u32` types
+/// - `#[vertex(integer)]` passes the data to the shader as integers, which
+///   is the default for `i32`/`u32` types. It can't be combined with
+///   `normalized`
+/// - `#[vertex(skip)]` leaves the field out
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    vertex::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,99 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitInt, LitStr};
+
+enum FieldKind {
+    Value,
+    Sampler(u32),
+    Skip,
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "Uniforms can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Uniforms can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut prefix = String::new();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("uniform")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("prefix") {
+                prefix = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("expected `prefix = \"...\"`"))
+            }
+        })?;
+    }
+
+    let mut names = Vec::new();
+    let mut setters = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let mut name = ident.to_string();
+        let mut kind = FieldKind::Value;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("uniform")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("sampler") {
+                    kind = FieldKind::Sampler(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                } else if meta.path.is_ident("skip") {
+                    kind = FieldKind::Skip;
+                } else {
+                    return Err(meta.error("expected `name = \"...\"`, `sampler = <unit>` or `skip`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let name = format!("{}{}", prefix, name);
+        match kind {
+            FieldKind::Skip => continue,
+            FieldKind::Value => setters.push(quote! {
+                program.set_uniform(#name, &self.#ident);
+            }),
+            FieldKind::Sampler(unit) => {
+                let unit_u32 = Literal::u32_suffixed(unit);
+                let unit_i32 = Literal::i32_suffixed(unit as i32);
+                setters.push(quote! {
+                    self.#ident.attach_to_unit(#unit_u32);
+                    program.set_uniform(#name, &#unit_i32);
+                });
+            }
+        }
+        names.push(name);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::render_gl::Uniforms for #ident #ty_generics #where_clause {
+            fn apply(&self, program: &::render_gl::Program) {
+                #[allow(unused_imports)]
+                use ::render_gl::TextureBinding as _;
+                program.set_used();
+                #(#setters)*
+            }
+
+            fn uniform_names() -> &'static [&'static str] {
+                &[#(#names),*]
+            }
+        }
+    })
+}
@@ -0,0 +1,137 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitInt};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "Vertex can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Vertex can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut is_repr_c = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                is_repr_c = true;
+            } else if meta.input.peek(syn::token::Paren) {
+                // skips the arguments of e.g. `align(16)`
+                let args;
+                syn::parenthesized!(args in meta.input);
+                args.parse::<TokenStream>()?;
+            }
+            Ok(())
+        })?;
+    }
+    if !is_repr_c {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Vertex types need #[repr(C)] so their layout is known",
+        ));
+    }
+
+    // the location after the previous field, which depends on how many
+    // locations its type takes, so it's only known once the constants are
+    // evaluated
+    let mut next_location = quote!(0);
+    let mut attributes = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let mut location = None;
+        let mut normalized = false;
+        let mut integer = None;
+        let mut skip = false;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("vertex")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("location") {
+                    location = Some(meta.value()?.parse::<LitInt>()?.base10_parse::<u32>()?);
+                } else if meta.path.is_ident("normalized") {
+                    normalized = true;
+                } else if meta.path.is_ident("integer") {
+                    integer = Some(meta.path.clone());
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else {
+                    return Err(meta.error(
+                        "expected `location = <n>`, `normalized`, `integer` or `skip`",
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+
+        if skip {
+            continue;
+        }
+        if let (true, Some(path)) = (normalized, &integer) {
+            return Err(syn::Error::new_spanned(
+                path,
+                "an attribute can't be both `integer` and `normalized`",
+            ));
+        }
+        // normalized integers are converted to floats, whatever the type's
+        // default is
+        let integer = match (integer.is_some(), normalized) {
+            (true, _) => quote!(true),
+            (false, true) => quote!(false),
+            (false, false) => quote!(<#ty as ::render_gl::buffer::VertexAttribType>::INTEGER),
+        };
+        // fields without a location follow on from the previous one
+        let location = match location {
+            Some(location) => quote!(#location),
+            None => next_location,
+        };
+        next_location = quote! {
+            (#location + <#ty as ::render_gl::buffer::VertexAttribType>::LOCATIONS)
+        };
+
+        attributes.push(quote! {
+            ::render_gl::buffer::VertexAttribute {
+                location: #location,
+                locations: <#ty as ::render_gl::buffer::VertexAttribType>::LOCATIONS,
+                components: <#ty as ::render_gl::buffer::VertexAttribType>::COMPONENTS,
+                gl_type: <#ty as ::render_gl::buffer::VertexAttribType>::GL_TYPE,
+                normalized: #normalized,
+                integer: #integer,
+                offset: ::core::mem::offset_of!(Self, #ident),
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // every field, skipped or not, has to be plain old data for the whole
+    // struct to be
+    let mut pod_where = where_clause.cloned().unwrap_or_else(|| syn::parse_quote!(where));
+    for field in fields {
+        let ty = &field.ty;
+        pod_where
+            .predicates
+            .push(syn::parse_quote!(#ty: ::render_gl::buffer::Pod));
+    }
+
+    Ok(quote! {
+        unsafe impl #impl_generics ::render_gl::buffer::Vertex for #ident #ty_generics #where_clause {
+            const ATTRIBUTES: &'static [::render_gl::buffer::VertexAttribute] = &[
+                #(#attributes),*
+            ];
+        }
+
+        unsafe impl #impl_generics ::render_gl::buffer::Pod for #ident #ty_generics #pod_where {}
+    })
+}
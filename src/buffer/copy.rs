@@ -0,0 +1,156 @@
+//! Reading buffers back to the CPU and copying between buffers on the GPU
+
+use super::*;
+
+impl<B, T> Buffer<B, T>
+where
+    B: BufferType,
+    T: Pod,
+{
+    /// Copies the `len()` elements of the buffer back into a `Vec<T>`,
+    /// waiting for any draws or dispatches writing to it
+    pub fn read(&self) -> Vec<T> {
+        self.read_range(0, self.len())
+            .expect("the whole buffer is always in range")
+    }
+
+    /// Copies `len` elements starting at element `offset` back into a
+    /// `Vec<T>`, failing if they go past `len()`
+    pub fn read_range(&self, offset: usize, len: usize) -> Result<Vec<T>, String> {
+        check_range("read", offset, len, self.len())?;
+
+        let mut data = Vec::<T>::with_capacity(len);
+        unsafe {
+            gl::BindBuffer(gl::COPY_READ_BUFFER, self.vbo);
+            gl::GetBufferSubData(
+                gl::COPY_READ_BUFFER,
+                byte_size::<T>(offset) as gl::types::GLintptr,
+                byte_size::<T>(len) as gl::types::GLsizeiptr,
+                data.as_mut_ptr() as *mut gl::types::GLvoid,
+            );
+            gl::BindBuffer(gl::COPY_READ_BUFFER, 0);
+            data.set_len(len);
+        }
+
+        Ok(data)
+    }
+}
+
+impl<B, T> Buffer<B, T>
+where
+    B: BufferType,
+    T: Copy,
+{
+    /// Copies `len` elements starting at element `src_offset` into `other`
+    /// at element `dst_offset`, without going through the CPU. The source
+    /// range has to be within `len()`, and the destination can't start past
+    /// `other.len()` or end past `other.capacity()`
+    pub fn copy_to<B2>(
+        &self,
+        other: &Buffer<B2, T>,
+        src_offset: usize,
+        dst_offset: usize,
+        len: usize,
+    ) -> Result<(), String>
+    where
+        B2: BufferType,
+    {
+        check_copy(
+            (src_offset, self.len()),
+            (dst_offset, other.len(), other.capacity()),
+            len,
+            self.vbo == other.vbo,
+        )?;
+
+        unsafe {
+            gl::BindBuffer(gl::COPY_READ_BUFFER, self.vbo);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, other.vbo);
+            gl::CopyBufferSubData(
+                gl::COPY_READ_BUFFER,
+                gl::COPY_WRITE_BUFFER,
+                byte_size::<T>(src_offset) as gl::types::GLintptr,
+                byte_size::<T>(dst_offset) as gl::types::GLintptr,
+                byte_size::<T>(len) as gl::types::GLsizeiptr,
+            );
+            gl::BindBuffer(gl::COPY_READ_BUFFER, 0);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+        }
+        other.len.set(other.len().max(dst_offset + len));
+
+        Ok(())
+    }
+}
+
+/// Checks that `len` elements starting at `offset` are within the `size`
+/// elements of a buffer, without overflowing
+fn check_range(action: &str, offset: usize, len: usize, size: usize) -> Result<usize, String> {
+    match offset.checked_add(len) {
+        Some(end) if end <= size => Ok(end),
+        _ => Err(format!(
+            "Can't {} {} elements at offset {} of a buffer with {} elements",
+            action, len, offset, size
+        )),
+    }
+}
+
+/// Checks a copy of `len` elements from `(offset, len)` of the source to
+/// `(offset, len, capacity)` of the destination
+fn check_copy(
+    (src_offset, src_len): (usize, usize),
+    (dst_offset, dst_len, dst_capacity): (usize, usize, usize),
+    len: usize,
+    same_buffer: bool,
+) -> Result<(), String> {
+    let src_end = check_range("copy", src_offset, len, src_len)?;
+    if dst_offset > dst_len {
+        return Err(format!(
+            "Can't copy to offset {}, past the end of a buffer with {} elements",
+            dst_offset, dst_len
+        ));
+    }
+    let dst_end = match dst_offset.checked_add(len) {
+        Some(end) if end <= dst_capacity => end,
+        _ => {
+            return Err(format!(
+                "Can't copy {} elements to offset {} of a buffer with room for {}",
+                len, dst_offset, dst_capacity
+            ))
+        }
+    };
+    if same_buffer && src_offset < dst_end && dst_offset < src_end {
+        return Err("Can't copy between overlapping ranges of the same buffer".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn copy_within_bounds() {
+        assert!(check_copy((2, 10), (0, 0, 8), 8, false).is_ok());
+        assert!(check_copy((0, 10), (4, 4, 8), 4, false).is_ok());
+    }
+
+    #[test]
+    fn copy_out_of_bounds() {
+        assert!(check_copy((4, 10), (0, 0, 16), 8, false).is_err());
+        assert!(check_copy((0, 10), (5, 4, 16), 2, false).is_err());
+        assert!(check_copy((0, 10), (4, 4, 8), 5, false).is_err());
+    }
+
+    #[test]
+    fn copy_overflowing_ranges() {
+        assert!(check_copy((usize::MAX, 10), (0, 0, 16), 2, false).is_err());
+        assert!(check_copy((0, 10), (4, 4, 16), usize::MAX, false).is_err());
+        assert!(check_range("read", 1, usize::MAX, 10).is_err());
+        assert_eq!(check_range("read", 2, 8, 10), Ok(10));
+    }
+
+    #[test]
+    fn copy_within_one_buffer() {
+        assert!(check_copy((0, 10), (5, 10, 10), 5, true).is_ok());
+        assert!(check_copy((0, 10), (4, 10, 10), 5, true).is_err());
+    }
+}
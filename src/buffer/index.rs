@@ -0,0 +1,41 @@
+//! Index types for element array buffers
+
+/// An integer type that can be used for indices in an element array buffer
+///
+/// # Safety
+/// `GL_TYPE` has to match the type's memory exactly
+pub unsafe trait IndexType: Copy {
+    const GL_TYPE: gl::types::GLenum;
+    /// The largest value, which ends a strip or fan and starts a new one
+    /// when primitive restart is enabled
+    const RESTART: Self;
+
+    fn to_usize(self) -> usize;
+}
+
+unsafe impl IndexType for u8 {
+    const GL_TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
+    const RESTART: Self = u8::MAX;
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+unsafe impl IndexType for u16 {
+    const GL_TYPE: gl::types::GLenum = gl::UNSIGNED_SHORT;
+    const RESTART: Self = u16::MAX;
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+unsafe impl IndexType for u32 {
+    const GL_TYPE: gl::types::GLenum = gl::UNSIGNED_INT;
+    const RESTART: Self = u32::MAX;
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
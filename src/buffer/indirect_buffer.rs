@@ -0,0 +1,142 @@
+//! Draw indirect buffers, holding draw commands that are read by the GPU,
+//! so many meshes packed into the same buffers can be drawn in one call and
+//! compute shaders can decide what gets drawn
+
+use super::*;
+
+pub struct BufferTypeDrawIndirect;
+impl BufferType for BufferTypeDrawIndirect {
+    const BUFFER_TYPE: gl::types::GLuint = gl::DRAW_INDIRECT_BUFFER;
+}
+
+pub type DrawIndirectBuffer<C = DrawElementsIndirectCommand> = Buffer<BufferTypeDrawIndirect, C>;
+
+/// The parameters of one indexed draw, laid out like
+/// `DrawElementsIndirectCommand` in the GL spec
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct DrawElementsIndirectCommand {
+    pub count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub base_vertex: i32,
+    pub base_instance: u32,
+}
+
+unsafe impl Pod for DrawElementsIndirectCommand {}
+
+/// The parameters of one non-indexed draw, laid out like
+/// `DrawArraysIndirectCommand` in the GL spec
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct DrawArraysIndirectCommand {
+    pub count: u32,
+    pub instance_count: u32,
+    pub first: u32,
+    pub base_instance: u32,
+}
+
+unsafe impl Pod for DrawArraysIndirectCommand {}
+
+/// The commands a draw indirect buffer can hold
+pub trait IndirectCommand: Copy {
+    /// Whether the command draws with an index buffer
+    const INDEXED: bool;
+}
+
+impl IndirectCommand for DrawElementsIndirectCommand {
+    const INDEXED: bool = true;
+}
+
+impl IndirectCommand for DrawArraysIndirectCommand {
+    const INDEXED: bool = false;
+}
+
+// all members are 4 bytes, so the commands can be written from a compute
+// shader as an array of structs in a std430 storage block
+unsafe impl Std430 for DrawElementsIndirectCommand {
+    const ALIGN: usize = 4;
+    const SIZE: usize = 20;
+}
+
+unsafe impl Std430 for DrawArraysIndirectCommand {
+    const ALIGN: usize = 4;
+    const SIZE: usize = 16;
+}
+
+impl<C> DrawIndirectBuffer<C>
+where
+    C: IndirectCommand,
+{
+    /// Binds the buffer to the shader storage binding point `index`, so a
+    /// compute shader can write the commands. Issue a
+    /// `gl::COMMAND_BARRIER_BIT` memory barrier before drawing with them
+    pub fn bind_storage(&self, index: gl::types::GLuint) {
+        unsafe {
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, index, self.vbo);
+        }
+    }
+}
+
+impl VertexArray {
+    /// Issues every command in `commands`, reading indices of type `I` from
+    /// the element array buffer bound to this vertex array. The commands
+    /// aren't checked against the buffers' sizes
+    pub fn multi_draw_elements_indirect<I>(
+        &self,
+        mode: gl::types::GLenum,
+        commands: &DrawIndirectBuffer<DrawElementsIndirectCommand>,
+    ) where
+        I: IndexType,
+    {
+        self.bind();
+        commands.bind();
+        unsafe {
+            gl::MultiDrawElementsIndirect(
+                mode,
+                I::GL_TYPE,
+                std::ptr::null(),
+                commands.len() as gl::types::GLsizei,
+                0,
+            );
+        }
+        commands.unbind();
+    }
+
+    /// Issues every command in `commands`. The commands aren't checked
+    /// against the buffers' sizes
+    pub fn multi_draw_arrays_indirect(
+        &self,
+        mode: gl::types::GLenum,
+        commands: &DrawIndirectBuffer<DrawArraysIndirectCommand>,
+    ) {
+        self.bind();
+        commands.bind();
+        unsafe {
+            gl::MultiDrawArraysIndirect(
+                mode,
+                std::ptr::null(),
+                commands.len() as gl::types::GLsizei,
+                0,
+            );
+        }
+        commands.unbind();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn commands_are_tightly_packed() {
+        assert_eq!(
+            std::mem::size_of::<DrawElementsIndirectCommand>(),
+            DrawElementsIndirectCommand::SIZE
+        );
+        assert_eq!(
+            std::mem::size_of::<DrawArraysIndirectCommand>(),
+            DrawArraysIndirectCommand::SIZE
+        );
+    }
+}
@@ -0,0 +1,360 @@
+//! Describes how Rust types map onto the std140 layout used by uniform
+//! blocks and the std430 layout used by shader storage blocks, so a struct
+//! can be copied into a buffer byte for byte and read back correctly by the
+//! shader.
+//!
+//! Scalars, `nalgebra_glm` vectors, `Mat4` and arrays are supported. `Mat3`
+//! isn't, since both layouts pad each of its columns to a `vec4` while the
+//! nalgebra type is tightly packed.
+
+use nalgebra_glm::{IVec2, IVec3, IVec4, Mat2, Mat4, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+
+use super::Pod;
+
+/// A type whose in-memory representation matches the std140 layout rules.
+///
+/// Structs should implement this with [`std140_struct!`](crate::std140_struct),
+/// which checks the layout at compile time.
+///
+/// # Safety
+/// `ALIGN` and `SIZE` must be the std140 base alignment and size of the type,
+/// and the Rust representation must match the std140 one byte for byte
+pub unsafe trait Std140: Copy {
+    const ALIGN: usize;
+    const SIZE: usize;
+}
+
+/// A type whose in-memory representation matches the std430 layout rules.
+///
+/// Structs should implement this with [`std430_struct!`](crate::std430_struct),
+/// which checks the layout at compile time.
+///
+/// # Safety
+/// `ALIGN` and `SIZE` must be the std430 base alignment and size of the type,
+/// and the Rust representation must match the std430 one byte for byte
+pub unsafe trait Std430: Copy {
+    const ALIGN: usize;
+    const SIZE: usize;
+}
+
+/// Rounds `offset` up to the next multiple of `align`
+pub const fn align_to(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+macro_rules! impl_layout {
+    ($layout:ident: $($ty:ty => ($align:expr, $size:expr)),* $(,)?) => {
+        $(
+            unsafe impl $layout for $ty {
+                const ALIGN: usize = $align;
+                const SIZE: usize = $size;
+            }
+        )*
+    };
+}
+
+impl_layout! { Std140:
+    f32 => (4, 4),
+    i32 => (4, 4),
+    u32 => (4, 4),
+    Vec2 => (8, 8),
+    IVec2 => (8, 8),
+    UVec2 => (8, 8),
+    Vec3 => (16, 12),
+    IVec3 => (16, 12),
+    UVec3 => (16, 12),
+    Vec4 => (16, 16),
+    IVec4 => (16, 16),
+    UVec4 => (16, 16),
+    Mat4 => (16, 64),
+}
+
+impl_layout! { Std430:
+    f32 => (4, 4),
+    i32 => (4, 4),
+    u32 => (4, 4),
+    Vec2 => (8, 8),
+    IVec2 => (8, 8),
+    UVec2 => (8, 8),
+    Vec3 => (16, 12),
+    IVec3 => (16, 12),
+    UVec3 => (16, 12),
+    Vec4 => (16, 16),
+    IVec4 => (16, 16),
+    UVec4 => (16, 16),
+    Mat2 => (8, 16),
+    Mat4 => (16, 64),
+}
+
+/// std140 rounds the stride of array elements up to 16 bytes, while a Rust
+/// array is tightly packed, so only elements that are already a multiple of
+/// 16 bytes (`Vec4`, `Mat4`, std140 structs...) can be used in arrays
+unsafe impl<T: Std140, const N: usize> Std140 for [T; N] {
+    const ALIGN: usize = {
+        assert!(
+            T::SIZE % 16 == 0,
+            "std140 array elements must be a multiple of 16 bytes, use Vec4 or a padded struct"
+        );
+        align_to(T::ALIGN, 16)
+    };
+    const SIZE: usize = N * T::SIZE;
+}
+
+/// std430 doesn't round the array stride up, but the elements still have to
+/// be a multiple of their own alignment (so `[Vec3; N]` is out)
+unsafe impl<T: Std430, const N: usize> Std430 for [T; N] {
+    const ALIGN: usize = {
+        assert!(
+            T::SIZE % T::ALIGN == 0,
+            "std430 array elements must be a multiple of their alignment, use Vec4 instead of Vec3"
+        );
+        T::ALIGN
+    };
+    const SIZE: usize = N * T::SIZE;
+}
+
+/// Explicit padding to put in front of a field that std140 places further
+/// along than Rust would
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct Padding<const N: usize>([u8; N]);
+
+impl<const N: usize> Default for Padding<N> {
+    fn default() -> Self {
+        Padding([0; N])
+    }
+}
+
+unsafe impl<const N: usize> Pod for Padding<N> {}
+
+unsafe impl<const N: usize> Std140 for Padding<N> {
+    const ALIGN: usize = 1;
+    const SIZE: usize = N;
+}
+
+unsafe impl<const N: usize> Std430 for Padding<N> {
+    const ALIGN: usize = 1;
+    const SIZE: usize = N;
+}
+
+/// Declares a `#[repr(C)]` struct to be used in a uniform block and checks
+/// at compile time that each field sits at the offset std140 expects. Where
+/// it doesn't, insert a [`Padding`] field in front of it.
+///
+/// ```
+/// use nalgebra_glm::{Mat4, Vec3};
+/// use render_gl::{buffer::Padding, std140_struct};
+///
+/// std140_struct! {
+///     #[derive(Clone, Copy)]
+///     pub struct Globals {
+///         pub view: Mat4,
+///         pub time: f32,
+///         _pad: Padding<12>,
+///         pub light_dir: Vec3,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! std140_struct {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($fvis:vis $field:ident : $fty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[repr(C, align(16))]
+        $vis struct $name {
+            $($fvis $field: $fty),*
+        }
+
+        // fails to compile if a field can't be read back from GPU memory
+        unsafe impl $crate::buffer::Pod for $name
+        where
+            $($fty: $crate::buffer::Pod),*
+        {}
+
+        unsafe impl $crate::buffer::Std140 for $name {
+            const ALIGN: usize = 16;
+            const SIZE: usize = ::std::mem::size_of::<$name>();
+        }
+
+        const _: () = {
+            #[allow(unused_mut, unused_assignments)]
+            let mut offset = 0_usize;
+            $(
+                offset = $crate::buffer::align_to(
+                    offset,
+                    <$fty as $crate::buffer::Std140>::ALIGN,
+                );
+                assert!(
+                    offset == ::std::mem::offset_of!($name, $field),
+                    concat!(
+                        "`", stringify!($name), "::", stringify!($field),
+                        "` isn't at its std140 offset, add Padding in front of it"
+                    )
+                );
+                offset += <$fty as $crate::buffer::Std140>::SIZE;
+            )*
+            assert!(
+                $crate::buffer::align_to(offset, 16) == ::std::mem::size_of::<$name>(),
+                concat!("`", stringify!($name), "` doesn't match its std140 size")
+            );
+        };
+    };
+}
+
+/// The std430 counterpart of [`std140_struct!`](crate::std140_struct), for
+/// structs used in shader storage blocks. Because the Rust struct is only as
+/// aligned as its most aligned field, a struct with `Vec3`/`Vec4` fields may
+/// need trailing [`Padding`] to reach its std430 size.
+///
+/// ```
+/// use nalgebra_glm::{Vec2, Vec3};
+/// use render_gl::{buffer::Padding, std430_struct};
+///
+/// std430_struct! {
+///     #[derive(Clone, Copy)]
+///     pub struct Particle {
+///         pub position: Vec3,
+///         pub age: f32,
+///         pub velocity: Vec2,
+///         _pad: Padding<8>,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! std430_struct {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($fvis:vis $field:ident : $fty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[repr(C)]
+        $vis struct $name {
+            $($fvis $field: $fty),*
+        }
+
+        // fails to compile if a field can't be read back from GPU memory
+        unsafe impl $crate::buffer::Pod for $name
+        where
+            $($fty: $crate::buffer::Pod),*
+        {}
+
+        unsafe impl $crate::buffer::Std430 for $name {
+            const ALIGN: usize = {
+                #[allow(unused_mut)]
+                let mut align = 1_usize;
+                $(
+                    if <$fty as $crate::buffer::Std430>::ALIGN > align {
+                        align = <$fty as $crate::buffer::Std430>::ALIGN;
+                    }
+                )*
+                align
+            };
+            const SIZE: usize = ::std::mem::size_of::<$name>();
+        }
+
+        const _: () = {
+            #[allow(unused_mut, unused_assignments)]
+            let mut offset = 0_usize;
+            $(
+                offset = $crate::buffer::align_to(
+                    offset,
+                    <$fty as $crate::buffer::Std430>::ALIGN,
+                );
+                assert!(
+                    offset == ::std::mem::offset_of!($name, $field),
+                    concat!(
+                        "`", stringify!($name), "::", stringify!($field),
+                        "` isn't at its std430 offset, add Padding in front of it"
+                    )
+                );
+                offset += <$fty as $crate::buffer::Std430>::SIZE;
+            )*
+            assert!(
+                $crate::buffer::align_to(offset, <$name as $crate::buffer::Std430>::ALIGN)
+                    == ::std::mem::size_of::<$name>(),
+                concat!("`", stringify!($name), "` doesn't match its std430 size, add trailing Padding")
+            );
+        };
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    crate::std140_struct! {
+        #[derive(Clone, Copy)]
+        struct Light {
+            position: Vec3,
+            intensity: f32,
+        }
+    }
+
+    crate::std140_struct! {
+        #[derive(Clone, Copy)]
+        struct Globals {
+            view: Mat4,
+            time: f32,
+            _pad: Padding<12>,
+            ambient: Vec3,
+            lights: [Light; 2],
+            offset: Vec2,
+        }
+    }
+
+    crate::std430_struct! {
+        #[derive(Clone, Copy)]
+        struct Particle {
+            position: Vec3,
+            age: f32,
+            velocity: Vec2,
+            _pad: Padding<8>,
+        }
+    }
+
+    crate::std430_struct! {
+        #[derive(Clone, Copy)]
+        struct Scalars {
+            a: f32,
+            b: [f32; 3],
+        }
+    }
+
+    #[test]
+    fn align_to_rounds_up() {
+        assert_eq!(align_to(0, 16), 0);
+        assert_eq!(align_to(4, 16), 16);
+        assert_eq!(align_to(16, 16), 16);
+        assert_eq!(align_to(12, 8), 16);
+    }
+
+    #[test]
+    fn struct_layout() {
+        assert_eq!(<Light as Std140>::SIZE, 16);
+        assert_eq!(std::mem::offset_of!(Globals, ambient), 80);
+        assert_eq!(std::mem::offset_of!(Globals, lights), 96);
+        assert_eq!(std::mem::offset_of!(Globals, offset), 128);
+        assert_eq!(<Globals as Std140>::SIZE, 144);
+    }
+
+    #[test]
+    fn std430_struct_layout() {
+        assert_eq!(<Particle as Std430>::ALIGN, 16);
+        assert_eq!(<Particle as Std430>::SIZE, 32);
+        assert_eq!(<[Particle; 4] as Std430>::SIZE, 128);
+        assert_eq!(<Scalars as Std430>::ALIGN, 4);
+        assert_eq!(<Scalars as Std430>::SIZE, 16);
+    }
+
+    #[test]
+    fn array_layout() {
+        assert_eq!(<[Vec4; 3] as Std140>::SIZE, 48);
+        assert_eq!(<[Mat4; 2] as Std140>::ALIGN, 16);
+    }
+}
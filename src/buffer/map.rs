@@ -0,0 +1,266 @@
+//! Mapping buffers into client memory, so data can be written straight into
+//! a buffer (or read out of it) without going through a `Vec`
+
+use gl;
+use super::*;
+use std::ops::{Deref, DerefMut};
+
+/// Options for mapping a buffer for writing
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MapFlags {
+    /// The previous contents of the mapped range are discarded, letting the
+    /// driver hand out fresh memory instead of waiting for draws using the
+    /// old contents. The mapped memory starts out undefined, so only write
+    /// to it
+    pub invalidate: bool,
+    /// The driver doesn't wait for pending draws that use the buffer. Only
+    /// write to ranges the GPU isn't reading from, and don't read the
+    /// mapped memory
+    pub unsynchronized: bool,
+    /// Writes only become visible to GL for ranges passed to
+    /// `BufferMapMut::flush`
+    pub flush_explicit: bool,
+}
+
+/// Read access to a mapped range of a buffer. Unmaps the buffer when
+/// dropped. It borrows the buffer mutably, since uploading to the buffer
+/// while it's mapped could give it new storage and leave the map dangling
+pub struct BufferMap<'a, T> {
+    vbo: gl::types::GLuint,
+    data: &'a [T],
+}
+
+/// Read and write access to a mapped range of a buffer. Unmaps the buffer
+/// when dropped
+pub struct BufferMapMut<'a, T> {
+    vbo: gl::types::GLuint,
+    data: &'a mut [T],
+}
+
+impl<B, T> Buffer<B, T>
+where
+    B: BufferType,
+    T: Pod,
+{
+    /// Maps the `len()` elements of the buffer for reading
+    pub fn map(&mut self) -> Result<BufferMap<'_, T>, String> {
+        self.map_range(0, self.len())
+    }
+
+    /// Maps `len` elements starting at element `offset` for reading
+    pub fn map_range(&mut self, offset: usize, len: usize) -> Result<BufferMap<'_, T>, String> {
+        let ptr = self.map_raw(offset, len, gl::MAP_READ_BIT)?;
+        Ok(BufferMap {
+            vbo: self.vbo,
+            data: unsafe { std::slice::from_raw_parts(ptr, len) },
+        })
+    }
+
+    /// Maps the `len()` elements of the buffer for writing
+    pub fn map_mut(&mut self, flags: MapFlags) -> Result<BufferMapMut<'_, T>, String> {
+        let whole = self.len() == self.capacity();
+        let ptr = self.map_raw(0, self.len(), access_bits(flags, whole))?;
+        Ok(BufferMapMut {
+            vbo: self.vbo,
+            data: unsafe { std::slice::from_raw_parts_mut(ptr, self.len()) },
+        })
+    }
+
+    /// Maps `len` elements starting at element `offset` for writing
+    pub fn map_range_mut(
+        &mut self,
+        offset: usize,
+        len: usize,
+        flags: MapFlags,
+    ) -> Result<BufferMapMut<'_, T>, String> {
+        let ptr = self.map_raw(offset, len, access_bits(flags, false))?;
+        Ok(BufferMapMut {
+            vbo: self.vbo,
+            data: unsafe { std::slice::from_raw_parts_mut(ptr, len) },
+        })
+    }
+
+    /// The buffer is mapped through the copy write target, like
+    /// `reallocate`, so mapping an element array buffer doesn't change the
+    /// bound vertex array
+    fn map_raw(
+        &self,
+        offset: usize,
+        len: usize,
+        access: gl::types::GLbitfield,
+    ) -> Result<*mut T, String> {
+        if len == 0 {
+            return Err("Can't map an empty range of a buffer".to_string());
+        }
+        if offset.checked_add(len).is_none_or(|end| end > self.len()) {
+            return Err(format!(
+                "Can't map {} elements at offset {} of a buffer with {} elements",
+                len,
+                offset,
+                self.len()
+            ));
+        }
+
+        let ptr = unsafe {
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.vbo);
+            let ptr = gl::MapBufferRange(
+                gl::COPY_WRITE_BUFFER,
+                byte_size::<T>(offset) as gl::types::GLintptr,
+                byte_size::<T>(len) as gl::types::GLsizeiptr,
+                access,
+            );
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+            ptr
+        };
+
+        if ptr.is_null() {
+            Err(format!(
+                "glMapBufferRange failed with error 0x{:x}, is the buffer already mapped?",
+                unsafe { gl::GetError() }
+            ))
+        } else {
+            Ok(ptr as *mut T)
+        }
+    }
+}
+
+/// `glMapBufferRange` access bits for a write mapping. `whole` says whether
+/// the range covers the buffer's whole storage, so all of it can be
+/// invalidated
+fn access_bits(flags: MapFlags, whole: bool) -> gl::types::GLbitfield {
+    let mut access = gl::MAP_WRITE_BIT;
+    // reading is an error when invalidating or unsynchronized
+    if !flags.invalidate && !flags.unsynchronized {
+        access |= gl::MAP_READ_BIT;
+    }
+    if flags.invalidate {
+        access |= if whole {
+            gl::MAP_INVALIDATE_BUFFER_BIT
+        } else {
+            gl::MAP_INVALIDATE_RANGE_BIT
+        };
+    }
+    if flags.unsynchronized {
+        access |= gl::MAP_UNSYNCHRONIZED_BIT;
+    }
+    if flags.flush_explicit {
+        access |= gl::MAP_FLUSH_EXPLICIT_BIT;
+    }
+    access
+}
+
+fn unmap(vbo: gl::types::GLuint) -> Result<(), String> {
+    let ok = unsafe {
+        gl::BindBuffer(gl::COPY_WRITE_BUFFER, vbo);
+        let ok = gl::UnmapBuffer(gl::COPY_WRITE_BUFFER);
+        gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+        ok
+    };
+
+    if ok == gl::TRUE {
+        Ok(())
+    } else {
+        Err("Buffer contents were lost while mapped and need uploading again".to_string())
+    }
+}
+
+impl<T> BufferMap<'_, T> {
+    /// Unmaps the buffer, reporting if its contents were corrupted while
+    /// mapped (which can happen on a display mode change)
+    pub fn unmap(self) -> Result<(), String> {
+        let vbo = self.vbo;
+        std::mem::forget(self);
+        unmap(vbo)
+    }
+}
+
+impl<T> BufferMapMut<'_, T> {
+    /// Makes writes to `len` elements starting at element `offset` of the
+    /// mapped range visible to GL. Only needed with `flush_explicit`
+    pub fn flush(&self, offset: usize, len: usize) {
+        assert!(
+            offset.checked_add(len).is_some_and(|end| end <= self.data.len()),
+            "Can't flush {} elements at offset {} of a mapping with {} elements",
+            len,
+            offset,
+            self.data.len()
+        );
+        unsafe {
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.vbo);
+            gl::FlushMappedBufferRange(
+                gl::COPY_WRITE_BUFFER,
+                byte_size::<T>(offset) as gl::types::GLintptr,
+                byte_size::<T>(len) as gl::types::GLsizeiptr,
+            );
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+        }
+    }
+
+    /// Unmaps the buffer, reporting if its contents were corrupted while
+    /// mapped (which can happen on a display mode change)
+    pub fn unmap(self) -> Result<(), String> {
+        let vbo = self.vbo;
+        std::mem::forget(self);
+        unmap(vbo)
+    }
+}
+
+impl<T> Deref for BufferMap<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.data
+    }
+}
+
+impl<T> Deref for BufferMapMut<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.data
+    }
+}
+
+impl<T> DerefMut for BufferMapMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.data
+    }
+}
+
+impl<T> Drop for BufferMap<'_, T> {
+    fn drop(&mut self) {
+        let _ = unmap(self.vbo);
+    }
+}
+
+impl<T> Drop for BufferMapMut<'_, T> {
+    fn drop(&mut self) {
+        let _ = unmap(self.vbo);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn write_only_when_invalidating() {
+        let flags = MapFlags { invalidate: true, ..Default::default() };
+        assert_eq!(access_bits(flags, true), gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_BUFFER_BIT);
+        assert_eq!(access_bits(flags, false), gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_RANGE_BIT);
+        assert_eq!(access_bits(MapFlags::default(), true), gl::MAP_WRITE_BIT | gl::MAP_READ_BIT);
+    }
+
+    #[test]
+    fn unsynchronized_flush_explicit() {
+        let flags = MapFlags {
+            invalidate: false,
+            unsynchronized: true,
+            flush_explicit: true,
+        };
+        assert_eq!(
+            access_bits(flags, false),
+            gl::MAP_WRITE_BIT | gl::MAP_UNSYNCHRONIZED_BIT | gl::MAP_FLUSH_EXPLICIT_BIT
+        );
+    }
+}
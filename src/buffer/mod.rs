@@ -1,17 +1,52 @@
+mod copy;
+mod index;
+mod indirect_buffer;
+mod layout;
+mod map;
 mod pixel_buffer;
+mod pod;
+mod storage_buffer;
+mod stream_buffer;
+mod uniform_buffer;
+mod vertex;
+
+pub use index::IndexType;
+pub use indirect_buffer::{
+    BufferTypeDrawIndirect, DrawArraysIndirectCommand, DrawElementsIndirectCommand, DrawIndirectBuffer,
+    IndirectCommand,
+};
+pub use layout::{align_to, Padding, Std140, Std430};
+pub use map::{BufferMap, BufferMapMut, MapFlags};
+pub use pixel_buffer::{BufferTypePixel, PendingRead, PixelBuffer};
+pub(crate) use pixel_buffer::packed_tightly;
+pub use pod::Pod;
+pub use storage_buffer::{BufferTypeShaderStorage, ShaderStorageBuffer};
+pub use stream_buffer::{StreamArrayBuffer, StreamBuffer, StreamElementArrayBuffer};
+pub use uniform_buffer::{BufferTypeUniform, UniformBlock, UniformBuffer};
+pub use vertex::{Vertex, VertexAttribType, VertexAttribute};
 
 use gl;
+use std::cell::Cell;
 
 pub trait BufferType {
     const BUFFER_TYPE: gl::types::GLuint;
 }
 
-pub struct Buffer<B>
+/// Buffer targets that have indexed binding points that shaders read from
+pub trait IndexedBufferType: BufferType {}
+
+/// A GL buffer holding elements of type `T`. The buffer remembers how many
+/// elements were uploaded (`len`) and how many fit in its storage
+/// (`capacity`), growing the storage when an upload doesn't fit
+pub struct Buffer<B, T>
 where
     B: BufferType,
 {
     vbo: gl::types::GLuint,
-    _hack: std::marker::PhantomData<B>, // need this otherwise the compiler complains about the unused 'B'
+    len: Cell<usize>,
+    capacity: Cell<usize>,
+    usage: Cell<gl::types::GLenum>,
+    _hack: std::marker::PhantomData<(B, T)>, // need this otherwise the compiler complains about the unused 'B' and 'T'
 }
 
 pub struct BufferTypeArray;
@@ -24,11 +59,12 @@ impl BufferType for BufferTypeElementArray {
     const BUFFER_TYPE: gl::types::GLuint = gl::ELEMENT_ARRAY_BUFFER;
 }
 
-impl<B> Buffer<B>
+impl<B, T> Buffer<B, T>
 where
     B: BufferType,
+    T: Copy,
 {
-    pub fn new() -> Buffer<B> {
+    pub fn new() -> Buffer<B, T> {
         use std::marker::PhantomData;
 
         let mut vbo: gl::types::GLuint = 0;
@@ -38,6 +74,9 @@ where
 
         Buffer {
             vbo,
+            len: Cell::new(0),
+            capacity: Cell::new(0),
+            usage: Cell::new(gl::DYNAMIC_DRAW),
             _hack: PhantomData,
         }
     }
@@ -55,45 +94,199 @@ where
         }
     }
 
+    /// The GL name of the buffer
+    pub fn id(&self) -> gl::types::GLuint {
+        self.vbo
+    }
+
+    /// Number of elements uploaded, which is what draw calls should use
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    /// Number of elements the buffer's storage has room for
+    pub fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+
+    /// Replaces the buffer's storage with `data`, hinting that it won't
+    /// change often
     #[allow(dead_code)]
-    pub fn static_draw_data<T>(&self, data: &[T]) {
-        unsafe {
+    pub fn static_draw_data(&self, data: &[T]) {
+        self.set_data(data, gl::STATIC_DRAW);
+    }
+
+    /// Replaces the buffer's storage with `data`, hinting that it will be
+    /// updated often
+    #[allow(dead_code)]
+    pub fn dynamic_draw_data(&self, data: &[T]) {
+        self.set_data(data, gl::DYNAMIC_DRAW);
+    }
+
+    /// Replaces the contents of the buffer with `data`, reusing the storage
+    /// when it's big enough
+    #[allow(dead_code)]
+    pub fn update_data(&self, data: &[T]) {
+        self.len.set(0);
+        self.update_range(0, data);
+    }
+
+    /// Writes `data` starting at element `offset`, growing the buffer if the
+    /// range goes past its capacity. `offset` can't be past `len()`, so the
+    /// buffer never has gaps of unwritten elements
+    pub fn update_range(&self, offset: usize, data: &[T]) {
+        assert!(
+            offset <= self.len.get(),
+            "update_range offset {} is past the end of the buffer ({} elements)",
+            offset,
+            self.len.get()
+        );
+
+        let end = offset + data.len();
+        if end > self.capacity.get() {
+            self.reserve(end);
+        }
+
+        self.upload(|| unsafe {
+            gl::BufferSubData(
+                gl::COPY_WRITE_BUFFER,
+                byte_size::<T>(offset) as gl::types::GLintptr,
+                std::mem::size_of_val(data) as gl::types::GLsizeiptr,
+                data.as_ptr() as *const gl::types::GLvoid,
+            );
+        });
+        self.len.set(self.len.get().max(end));
+    }
+
+    /// Makes sure the buffer has room for at least `capacity` elements,
+    /// keeping its contents
+    pub fn reserve(&self, capacity: usize) {
+        if capacity > self.capacity.get() {
+            self.reallocate(grown_capacity(self.capacity.get(), capacity));
+        }
+    }
+
+    /// Replaces the buffer's storage with room for `len` elements, which
+    /// start out undefined and count towards `len()`. Useful for buffers the
+    /// GPU writes into
+    pub fn allocate(&self, len: usize, usage: gl::types::GLenum) {
+        self.upload(|| unsafe {
             gl::BufferData(
-                B::BUFFER_TYPE,
-                (data.len() * ::std::mem::size_of::<T>()) as gl::types::GLsizeiptr,
+                gl::COPY_WRITE_BUFFER,
+                byte_size::<T>(len) as gl::types::GLsizeiptr,
+                std::ptr::null(),
+                usage,
+            );
+        });
+        self.len.set(len);
+        self.capacity.set(len);
+        self.usage.set(usage);
+    }
+
+    fn set_data(&self, data: &[T], usage: gl::types::GLenum) {
+        self.upload(|| unsafe {
+            gl::BufferData(
+                gl::COPY_WRITE_BUFFER,
+                std::mem::size_of_val(data) as gl::types::GLsizeiptr,
                 data.as_ptr() as *const gl::types::GLvoid,
-                gl::STATIC_DRAW,
+                usage,
             );
+        });
+        self.len.set(data.len());
+        self.capacity.set(data.len());
+        self.usage.set(usage);
+    }
+
+    /// Runs `upload` with the buffer bound to `gl::COPY_WRITE_BUFFER`.
+    /// Binding it to `B::BUFFER_TYPE` instead would, for element array
+    /// buffers, replace the index buffer of whatever vertex array is bound
+    fn upload(&self, upload: impl FnOnce()) {
+        unsafe {
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.vbo);
+        }
+        upload();
+        unsafe {
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
         }
     }
 
-    #[allow(dead_code)]
-    pub fn dynamic_draw_data<T>(&self, data: &[T]) {
+    /// Gives the buffer new storage for `capacity` elements, copying the
+    /// current contents over on the GPU. The buffer keeps its name, so
+    /// vertex arrays pointing at it stay valid. The copy targets are used so
+    /// that whatever is bound to `B::BUFFER_TYPE` is left alone
+    fn reallocate(&self, capacity: usize) {
+        let bytes = byte_size::<T>(self.len.get()) as gl::types::GLsizeiptr;
         unsafe {
+            let mut scratch: gl::types::GLuint = 0;
+            if bytes > 0 {
+                gl::GenBuffers(1, &mut scratch);
+                gl::BindBuffer(gl::COPY_WRITE_BUFFER, scratch);
+                gl::BufferData(gl::COPY_WRITE_BUFFER, bytes, std::ptr::null(), gl::STREAM_COPY);
+                gl::BindBuffer(gl::COPY_READ_BUFFER, self.vbo);
+                gl::CopyBufferSubData(gl::COPY_READ_BUFFER, gl::COPY_WRITE_BUFFER, 0, 0, bytes);
+            }
+
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.vbo);
             gl::BufferData(
-                B::BUFFER_TYPE,
-                (data.len() * ::std::mem::size_of::<T>()) as gl::types::GLsizeiptr,
-                data.as_ptr() as *const gl::types::GLvoid,
-                gl::DYNAMIC_DRAW,
+                gl::COPY_WRITE_BUFFER,
+                byte_size::<T>(capacity) as gl::types::GLsizeiptr,
+                std::ptr::null(),
+                self.usage.get(),
             );
+
+            if bytes > 0 {
+                gl::BindBuffer(gl::COPY_READ_BUFFER, scratch);
+                gl::CopyBufferSubData(gl::COPY_READ_BUFFER, gl::COPY_WRITE_BUFFER, 0, 0, bytes);
+                gl::DeleteBuffers(1, &scratch);
+            }
+            gl::BindBuffer(gl::COPY_READ_BUFFER, 0);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+        }
+        self.capacity.set(capacity);
+    }
+}
+
+impl<B, T> Buffer<B, T>
+where
+    B: IndexedBufferType,
+{
+    /// Binds the whole buffer to the indexed binding point `index`
+    pub fn bind_base(&self, index: gl::types::GLuint) {
+        unsafe {
+            gl::BindBufferBase(B::BUFFER_TYPE, index, self.vbo);
         }
     }
 
-    /// TODO not sure this is working correctly
-    #[allow(dead_code)]
-    pub fn update_data<T>(&self, data:&[T]) {
-        self.bind();
+    /// Binds `size` bytes starting at `offset` to the indexed binding point
+    /// `index`. The offset has to respect the target's offset alignment
+    pub fn bind_range(&self, index: gl::types::GLuint, offset: usize, size: usize) {
         unsafe {
-            gl::BufferSubData(
-                gl::ARRAY_BUFFER, 
-                0,
-                (data.len() * ::std::mem::size_of::<T>()) as gl::types::GLsizeiptr,
-                data.as_ptr() as *const gl::types::GLvoid);
+            gl::BindBufferRange(
+                B::BUFFER_TYPE,
+                index,
+                self.vbo,
+                offset as gl::types::GLintptr,
+                size as gl::types::GLsizeiptr,
+            );
         }
     }
 }
 
-impl<B> Drop for Buffer<B>
+impl<B, T> Default for Buffer<B, T>
+where
+    B: BufferType,
+    T: Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B, T> Drop for Buffer<B, T>
 where
     B: BufferType,
 {
@@ -104,8 +297,18 @@ where
     }
 }
 
-pub type ArrayBuffer = Buffer<BufferTypeArray>;
-pub type ElementArrayBuffer = Buffer<BufferTypeElementArray>;
+fn byte_size<T>(count: usize) -> usize {
+    count * std::mem::size_of::<T>()
+}
+
+/// Capacity to grow to when `required` elements don't fit in `capacity`.
+/// Doubling keeps repeated appends from reallocating every time
+fn grown_capacity(capacity: usize, required: usize) -> usize {
+    required.max(capacity * 2)
+}
+
+pub type ArrayBuffer<T = f32> = Buffer<BufferTypeArray, T>;
+pub type ElementArrayBuffer<T = u32> = Buffer<BufferTypeElementArray, T>;
 
 pub struct VertexArray {
     vao: gl::types::GLuint,
@@ -132,12 +335,99 @@ impl VertexArray {
             gl::BindVertexArray(0);
         }
     }
+
+    /// Points every attribute of `V` at `buffer`, which should hold `V`s.
+    /// Leaves this vertex array bound, so an element buffer can be bound to
+    /// it next
+    pub fn configure_attributes<V>(&self, buffer: &ArrayBuffer<V>)
+    where
+        V: Vertex,
+    {
+        self.bind();
+        buffer.bind();
+        vertex::set_attribute_pointers::<V>(0);
+        buffer.unbind();
+    }
+
+    /// Like `configure_attributes`, but the attributes advance once per
+    /// instance rather than per vertex, for per-instance data like
+    /// transforms and colors in instanced draws
+    pub fn configure_instance_attributes<V>(&self, buffer: &ArrayBuffer<V>)
+    where
+        V: Vertex,
+    {
+        self.bind();
+        buffer.bind();
+        vertex::set_attribute_pointers::<V>(1);
+        buffer.unbind();
+    }
+
+    /// Draws `instance_count` instances of `count` vertices starting at
+    /// `first`, with primitive `mode` such as `gl::TRIANGLES`
+    pub fn draw_arrays_instanced(
+        &self,
+        mode: gl::types::GLenum,
+        first: usize,
+        count: usize,
+        instance_count: usize,
+    ) {
+        self.bind();
+        unsafe {
+            gl::DrawArraysInstanced(
+                mode,
+                first as gl::types::GLint,
+                count as gl::types::GLsizei,
+                instance_count as gl::types::GLsizei,
+            );
+        }
+    }
+
+    /// Draws `instance_count` instances of every index in `indices`, which
+    /// is bound to this vertex array
+    pub fn draw_elements_instanced<I>(
+        &self,
+        mode: gl::types::GLenum,
+        indices: &ElementArrayBuffer<I>,
+        instance_count: usize,
+    ) where
+        I: IndexType,
+    {
+        self.bind();
+        indices.bind();
+        unsafe {
+            gl::DrawElementsInstanced(
+                mode,
+                indices.len() as gl::types::GLsizei,
+                I::GL_TYPE,
+                std::ptr::null(),
+                instance_count as gl::types::GLsizei,
+            );
+        }
+    }
+}
+
+impl Default for VertexArray {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Drop for VertexArray {
     fn drop(&mut self) {
         unsafe {
-            gl::DeleteVertexArrays(1, &mut self.vao);
+            gl::DeleteVertexArrays(1, &self.vao);
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn growth_doubles_capacity() {
+        assert_eq!(grown_capacity(0, 3), 3);
+        assert_eq!(grown_capacity(4, 5), 8);
+        assert_eq!(grown_capacity(4, 20), 20);
+    }
+}
@@ -1,28 +1,115 @@
-//! The purpose of this module is to define a PixelBuffer that can 
-//! retrieve texture data from openGL
+//! Pixel pack buffers, for reading texture and framebuffer data back from
+//! the GPU without stalling the pipeline until the data is needed
 
 use gl;
 use super::*;
+use crate::{ColorF32, ColorU8, Fence, RGBImage};
+use std::time::Duration;
 
 pub struct BufferTypePixel;
 impl BufferType for BufferTypePixel {
     const BUFFER_TYPE: gl::types::GLuint = gl::PIXEL_PACK_BUFFER;
 }
 
-#[allow(dead_code)]
-pub type PixelBuffer = Buffer<BufferTypePixel>;
-
-#[allow(dead_code)]
-impl PixelBuffer {
-    pub fn get_texture_data(&self, size: usize) -> Vec<u32> {
-        let mut data = vec!(0u32; size);
-        unsafe {
-            gl::GetBufferSubData(gl::TEXTURE_2D,
-                0,
-                ((size as usize) * std::mem::size_of::<u32>()) as gl::types::GLsizeiptr,
-                data.as_mut_ptr() as *mut gl::types::GLvoid)
-            }
-            
-        todo!();
-    }
-}
\ No newline at end of file
+pub type PixelBuffer<T> = Buffer<BufferTypePixel, T>;
+
+/// A read of `width * height` pixels into a pixel buffer that the GPU may
+/// still be working on. Poll it with `is_ready` once a frame, then take the
+/// data, which only blocks if the read hasn't finished
+pub struct PendingRead<TData>
+where
+    TData: Pod,
+{
+    buffer: PixelBuffer<TData>,
+    fence: Fence,
+    width: usize,
+    height: usize,
+}
+
+impl<TData> PendingRead<TData>
+where
+    TData: Pod,
+{
+    /// Runs `read` with a pixel buffer big enough for the pixels bound, so
+    /// that a `glGetTexImage` or `glReadPixels` in it writes into the buffer
+    pub(crate) fn new<F>(width: usize, height: usize, read: F) -> Self
+    where
+        F: FnOnce(),
+    {
+        let buffer = PixelBuffer::new();
+        buffer.allocate(width * height, gl::STREAM_READ);
+        buffer.bind();
+        packed_tightly(read);
+        buffer.unbind();
+
+        PendingRead {
+            buffer,
+            fence: Fence::new(),
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether the GPU has finished writing the pixels
+    pub fn is_ready(&self) -> bool {
+        self.fence.is_signaled()
+    }
+
+    /// Waits up to `timeout` for the GPU to finish writing the pixels,
+    /// returning whether it did
+    pub fn wait(&self, timeout: Duration) -> bool {
+        self.fence.wait(timeout)
+    }
+
+    /// Copies the pixels out of the buffer, bottom row first like
+    /// `Texture::get_pixel_data`. Blocks until the read has finished
+    pub fn into_data(self) -> Result<Vec<TData>, String> {
+        // an empty range can't be mapped
+        if self.width * self.height == 0 {
+            return Ok(Vec::new());
+        }
+        let mut buffer = self.buffer;
+        let data = buffer.map()?.to_vec();
+        Ok(data)
+    }
+}
+
+/// Runs `read` with rows packed without padding, then puts the caller's
+/// `PACK_ALIGNMENT` back. Rows of odd sized pixels aren't padded to 4 bytes
+/// in a `Vec`
+pub(crate) fn packed_tightly<R>(read: impl FnOnce() -> R) -> R {
+    let mut alignment: gl::types::GLint = 0;
+    unsafe {
+        gl::GetIntegerv(gl::PACK_ALIGNMENT, &mut alignment);
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+    }
+    let result = read();
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, alignment);
+    }
+    result
+}
+
+impl PendingRead<[u8; 3]> {
+    pub fn into_rgb_image(self) -> Result<RGBImage, String> {
+        let (width, height) = (self.width, self.height);
+        let data = self.into_data()?.iter().map(ColorU8::from).collect();
+        Ok(RGBImage::new_u8(data, width, height))
+    }
+}
+
+impl PendingRead<[f32; 3]> {
+    pub fn into_rgb_image(self) -> Result<RGBImage, String> {
+        let (width, height) = (self.width, self.height);
+        let data = self.into_data()?.iter().map(ColorF32::from).collect();
+        Ok(RGBImage::new_f32(data, width, height))
+    }
+}
@@ -0,0 +1,31 @@
+//! Plain old data, the types that can be viewed straight from buffer memory
+//! that the GPU wrote to
+
+use nalgebra_glm::{IVec2, IVec3, IVec4, Mat2, Mat3, Mat4, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+
+/// A type that any bit pattern of the right size is a valid value of, so it
+/// can be read back from a mapped buffer or `glGetBufferSubData`.
+///
+/// Vertex structs get an implementation from `#[derive(Vertex)]`, and
+/// std140/std430 structs from their declaring macros.
+///
+/// # Safety
+/// Every bit pattern of `size_of::<Self>()` bytes must be a valid `Self`,
+/// which rules out `bool`, `char`, enums, references and pointers
+pub unsafe trait Pod: Copy {}
+
+macro_rules! impl_pod {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            unsafe impl Pod for $ty {}
+        )*
+    };
+}
+
+impl_pod! {
+    u8, i8, u16, i16, u32, i32, u64, i64, f32, f64,
+    Vec2, Vec3, Vec4, IVec2, IVec3, IVec4, UVec2, UVec3, UVec4,
+    Mat2, Mat3, Mat4,
+}
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
@@ -0,0 +1,16 @@
+//! Shader storage buffers, for large per-instance data and for results
+//! written by compute shaders that need to be read back on the CPU
+
+use gl;
+use super::*;
+
+pub struct BufferTypeShaderStorage;
+impl BufferType for BufferTypeShaderStorage {
+    const BUFFER_TYPE: gl::types::GLuint = gl::SHADER_STORAGE_BUFFER;
+}
+impl IndexedBufferType for BufferTypeShaderStorage {}
+
+/// Read results back with [`Buffer::read`]. Issue a
+/// `gl::SHADER_STORAGE_BARRIER_BIT` memory barrier first if a compute shader
+/// wrote to it
+pub type ShaderStorageBuffer<T> = Buffer<BufferTypeShaderStorage, T>;
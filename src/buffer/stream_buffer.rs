@@ -0,0 +1,193 @@
+//! Ring buffers for geometry that changes every frame. Each push is written
+//! into the next free range of one buffer, and fences stop a range being
+//! overwritten while draws from earlier frames may still read it
+
+use super::*;
+use crate::Fence;
+use std::collections::VecDeque;
+
+/// A buffer that hands out ranges for per-frame data. Push each batch of
+/// vertices or indices, draw with the returned offset, and call `end_frame`
+/// once the frame's draws have been issued
+pub struct StreamBuffer<B, T>
+where
+    B: BufferType,
+    T: Pod,
+{
+    buffer: Buffer<B, T>,
+    ring: Ring,
+    /// One fence per frame in `Ring::in_flight`
+    fences: VecDeque<Fence>,
+}
+
+pub type StreamArrayBuffer<T = f32> = StreamBuffer<BufferTypeArray, T>;
+pub type StreamElementArrayBuffer<T = u32> = StreamBuffer<BufferTypeElementArray, T>;
+
+impl<B, T> StreamBuffer<B, T>
+where
+    B: BufferType,
+    T: Pod,
+{
+    /// A ring of `capacity` elements. A frame can't push more than this, and
+    /// a few frames' worth lets the CPU run ahead of the GPU without waiting
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "A StreamBuffer needs room for at least one element");
+        let buffer = Buffer::new();
+        buffer.allocate(capacity, gl::STREAM_DRAW);
+
+        StreamBuffer {
+            buffer,
+            ring: Ring::new(capacity),
+            fences: VecDeque::new(),
+        }
+    }
+
+    /// The underlying buffer, for binding and configuring vertex arrays
+    pub fn buffer(&self) -> &Buffer<B, T> {
+        &self.buffer
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Writes `data` into the next free range and returns its offset in
+    /// elements, which is the first vertex or index to draw from. Waits
+    /// for the GPU if the range is still used by an earlier frame, and
+    /// fails if this frame has already filled the buffer or waiting fails
+    pub fn push(&mut self, data: &[T]) -> Result<usize, String> {
+        let (offset, retired) = self.ring.allocate(data.len())?;
+        for fence in self.fences.drain(..retired) {
+            fence.wait_signaled()?;
+        }
+
+        if !data.is_empty() {
+            let flags = MapFlags {
+                invalidate: true,
+                unsynchronized: true,
+                flush_explicit: false,
+            };
+            let mut map = self.buffer.map_range_mut(offset, data.len(), flags)?;
+            map.copy_from_slice(data);
+            map.unmap()?;
+        }
+
+        Ok(offset)
+    }
+
+    /// Marks the end of the frame's draws, so the ranges pushed during it
+    /// are reused once the GPU is done with them
+    pub fn end_frame(&mut self) {
+        if self.ring.end_frame() {
+            self.fences.push_back(Fence::new());
+        }
+    }
+}
+
+/// The bookkeeping of a `StreamBuffer`. Positions grow forever and wrap
+/// onto the buffer modulo the capacity, so a range written at position `p`
+/// reuses the memory of position `p - capacity`
+struct Ring {
+    capacity: usize,
+    head: usize,
+    frame_start: usize,
+    /// Start of every ended frame the GPU may still be reading, oldest first
+    in_flight: VecDeque<usize>,
+}
+
+impl Ring {
+    fn new(capacity: usize) -> Self {
+        Ring {
+            capacity,
+            head: 0,
+            frame_start: 0,
+            in_flight: VecDeque::new(),
+        }
+    }
+
+    /// Reserves `len` elements, returning their offset in the buffer and
+    /// how many in-flight frames have to finish before they're written
+    fn allocate(&mut self, len: usize) -> Result<(usize, usize), String> {
+        let mut start = self.head;
+        if start % self.capacity + len > self.capacity {
+            // ranges can't straddle the end of the buffer
+            start = start.next_multiple_of(self.capacity);
+        }
+        let end = start + len;
+
+        if end - self.frame_start > self.capacity {
+            return Err(format!(
+                "Streaming {} more elements would overwrite this frame's data, the buffer only holds {}",
+                len, self.capacity
+            ));
+        }
+
+        let reused_end = end.saturating_sub(self.capacity);
+        let retired = self
+            .in_flight
+            .iter()
+            .take_while(|&&frame_start| frame_start < reused_end)
+            .count();
+        self.in_flight.drain(..retired);
+
+        self.head = end;
+        Ok((start % self.capacity, retired))
+    }
+
+    /// Returns whether the frame used any of the buffer and needs a fence
+    fn end_frame(&mut self) -> bool {
+        if self.head == self.frame_start {
+            return false;
+        }
+        self.in_flight.push_back(self.frame_start);
+        self.frame_start = self.head;
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn allocations_wrap_at_the_end() {
+        let mut ring = Ring::new(10);
+        assert_eq!(ring.allocate(4), Ok((0, 0)));
+        assert_eq!(ring.allocate(4), Ok((4, 0)));
+        assert!(ring.end_frame());
+        // 2 elements left at the end aren't enough
+        assert_eq!(ring.allocate(3), Ok((0, 1)));
+    }
+
+    #[test]
+    fn waits_only_for_overwritten_frames() {
+        let mut ring = Ring::new(12);
+        for _ in 0..3 {
+            ring.allocate(4).unwrap();
+            ring.end_frame();
+        }
+        assert_eq!(ring.allocate(4), Ok((0, 1)));
+        assert_eq!(ring.allocate(2), Ok((4, 1)));
+        assert_eq!(ring.allocate(2), Ok((6, 0)));
+        assert_eq!(ring.in_flight.len(), 1);
+    }
+
+    #[test]
+    fn frame_cant_overwrite_itself() {
+        let mut ring = Ring::new(8);
+        ring.allocate(4).unwrap();
+        ring.end_frame();
+        assert_eq!(ring.allocate(4), Ok((4, 0)));
+        assert_eq!(ring.allocate(3), Ok((0, 1)));
+        assert!(ring.allocate(2).is_err());
+        assert_eq!(ring.allocate(1), Ok((3, 0)));
+    }
+
+    #[test]
+    fn empty_frames_need_no_fence() {
+        let mut ring = Ring::new(8);
+        assert!(!ring.end_frame());
+        ring.allocate(1).unwrap();
+        assert!(ring.end_frame());
+    }
+}
@@ -0,0 +1,101 @@
+//! Uniform buffers, for sharing a block of uniforms (camera matrices, time,
+//! lights...) between every program that declares a matching uniform block
+
+use gl;
+use super::*;
+use crate::Program;
+
+pub struct BufferTypeUniform;
+impl BufferType for BufferTypeUniform {
+    const BUFFER_TYPE: gl::types::GLuint = gl::UNIFORM_BUFFER;
+}
+impl IndexedBufferType for BufferTypeUniform {}
+
+pub type UniformBuffer<T> = Buffer<BufferTypeUniform, T>;
+
+/// A uniform buffer holding a single std140 `T`, bound to a fixed binding
+/// point. Updating it once is enough for every program attached to it
+pub struct UniformBlock<T>
+where
+    T: Std140,
+{
+    buffer: UniformBuffer<T>,
+    binding: gl::types::GLuint,
+}
+
+impl<T> UniformBlock<T>
+where
+    T: Std140,
+{
+    pub fn new(binding: gl::types::GLuint, value: &T) -> Self {
+        // the block can be bigger than `T`, and binding less than the
+        // whole block is undefined
+        let buffer = UniformBuffer::new();
+        buffer.allocate(block_len::<T>(), gl::DYNAMIC_DRAW);
+        buffer.update_data(std::slice::from_ref(value));
+        buffer.bind_base(binding);
+
+        UniformBlock { buffer, binding }
+    }
+
+    pub fn binding(&self) -> gl::types::GLuint {
+        self.binding
+    }
+
+    pub fn update(&self, value: &T) {
+        self.buffer.update_data(std::slice::from_ref(value));
+    }
+
+    /// Points the uniform block called `block_name` in `program` at this
+    /// buffer, failing if the program has no such block or its size
+    /// doesn't match `T`
+    pub fn attach(&self, program: &Program, block_name: &str) -> Result<(), String> {
+        match program.uniform_block_data_size(block_name) {
+            None => return Err(format!("Program has no uniform block named `{}`", block_name)),
+            Some(size) if size != block_size::<T>() => {
+                return Err(format!(
+                    "Uniform block `{}` is {} bytes but the Rust type is {} bytes",
+                    block_name,
+                    size,
+                    block_size::<T>()
+                ))
+            }
+            Some(_) => {}
+        }
+
+        program.bind_uniform_block(block_name, self.binding)
+    }
+}
+
+/// A std140 block is padded out to a multiple of a vec4, so that's the size
+/// the driver reports for it
+fn block_size<T: Std140>() -> usize {
+    align_to(T::SIZE, 16)
+}
+
+/// Number of `T`s the buffer needs to cover the whole block
+fn block_len<T: Std140>() -> usize {
+    block_size::<T>().div_ceil(std::mem::size_of::<T>())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nalgebra_glm::{Vec3, Vec4};
+
+    #[test]
+    fn block_size_rounds_up_to_vec4() {
+        assert_eq!(block_size::<f32>(), 16);
+        assert_eq!(block_size::<Vec3>(), 16);
+        assert_eq!(block_size::<Vec4>(), 16);
+        assert_eq!(block_size::<[Vec4; 3]>(), 48);
+    }
+
+    #[test]
+    fn buffer_covers_the_block() {
+        assert_eq!(block_len::<f32>(), 4);
+        assert_eq!(block_len::<Vec3>(), 2);
+        assert_eq!(block_len::<Vec4>(), 1);
+        assert_eq!(block_len::<[Vec4; 3]>(), 1);
+    }
+}
@@ -0,0 +1,275 @@
+//! Describes the memory layout of vertex types, so a `VertexArray` can set
+//! up every attribute pointer from the type alone
+
+use nalgebra_glm::{IVec2, IVec3, IVec4, Mat2, Mat3, Mat4, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+
+/// One attribute of a vertex type, as handed to `glVertexAttribPointer`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub location: gl::types::GLuint,
+    /// Number of consecutive locations used. Matrices take one per column,
+    /// each with `components` values
+    pub locations: gl::types::GLuint,
+    pub components: gl::types::GLint,
+    pub gl_type: gl::types::GLenum,
+    /// Whether integer data is mapped to `[0, 1]`/`[-1, 1]` when converted
+    /// to floats
+    pub normalized: bool,
+    /// Whether the shader reads the data as integers, through
+    /// `glVertexAttribIPointer`, instead of converting it to floats
+    pub integer: bool,
+    /// Offset of the attribute from the start of the vertex, in bytes
+    pub offset: usize,
+}
+
+/// A vertex type whose attributes can be configured automatically, usually
+/// implemented with `#[derive(Vertex)]` from the `derive` feature.
+///
+/// # Safety
+/// Every attribute has to lie within the type, which has to be `#[repr(C)]`
+pub unsafe trait Vertex: Copy {
+    const ATTRIBUTES: &'static [VertexAttribute];
+}
+
+/// A type that can be used as a single vertex attribute
+///
+/// # Safety
+/// The constants have to describe the type's memory exactly
+pub unsafe trait VertexAttribType {
+    /// Number of locations the attribute takes, the number of columns for
+    /// matrices
+    const LOCATIONS: gl::types::GLuint = 1;
+    const COMPONENTS: gl::types::GLint;
+    const GL_TYPE: gl::types::GLenum;
+    /// Whether the attribute is read as integers unless asked otherwise
+    const INTEGER: bool;
+}
+
+macro_rules! impl_vertex_attrib_type {
+    ($($ty:ty => ($components:expr, $gl_type:expr, $integer:expr)),* $(,)?) => {
+        $(
+            unsafe impl VertexAttribType for $ty {
+                const COMPONENTS: gl::types::GLint = $components;
+                const GL_TYPE: gl::types::GLenum = $gl_type;
+                const INTEGER: bool = $integer;
+            }
+        )*
+    };
+}
+
+impl_vertex_attrib_type! {
+    f32 => (1, gl::FLOAT, false),
+    [f32; 2] => (2, gl::FLOAT, false),
+    [f32; 3] => (3, gl::FLOAT, false),
+    [f32; 4] => (4, gl::FLOAT, false),
+    Vec2 => (2, gl::FLOAT, false),
+    Vec3 => (3, gl::FLOAT, false),
+    Vec4 => (4, gl::FLOAT, false),
+    i32 => (1, gl::INT, true),
+    [i32; 2] => (2, gl::INT, true),
+    [i32; 3] => (3, gl::INT, true),
+    [i32; 4] => (4, gl::INT, true),
+    IVec2 => (2, gl::INT, true),
+    IVec3 => (3, gl::INT, true),
+    IVec4 => (4, gl::INT, true),
+    u32 => (1, gl::UNSIGNED_INT, true),
+    [u32; 2] => (2, gl::UNSIGNED_INT, true),
+    [u32; 3] => (3, gl::UNSIGNED_INT, true),
+    [u32; 4] => (4, gl::UNSIGNED_INT, true),
+    UVec2 => (2, gl::UNSIGNED_INT, true),
+    UVec3 => (3, gl::UNSIGNED_INT, true),
+    UVec4 => (4, gl::UNSIGNED_INT, true),
+    // smaller integers are usually colors or packed normals, so they're
+    // converted to floats unless the attribute is marked `integer`
+    u8 => (1, gl::UNSIGNED_BYTE, false),
+    [u8; 2] => (2, gl::UNSIGNED_BYTE, false),
+    [u8; 3] => (3, gl::UNSIGNED_BYTE, false),
+    [u8; 4] => (4, gl::UNSIGNED_BYTE, false),
+    i8 => (1, gl::BYTE, false),
+    [i8; 2] => (2, gl::BYTE, false),
+    [i8; 3] => (3, gl::BYTE, false),
+    [i8; 4] => (4, gl::BYTE, false),
+    u16 => (1, gl::UNSIGNED_SHORT, false),
+    [u16; 2] => (2, gl::UNSIGNED_SHORT, false),
+    [u16; 3] => (3, gl::UNSIGNED_SHORT, false),
+    [u16; 4] => (4, gl::UNSIGNED_SHORT, false),
+    i16 => (1, gl::SHORT, false),
+    [i16; 2] => (2, gl::SHORT, false),
+    [i16; 3] => (3, gl::SHORT, false),
+    [i16; 4] => (4, gl::SHORT, false),
+    crate::ColorF32 => (3, gl::FLOAT, false),
+    crate::ColorU8 => (3, gl::UNSIGNED_BYTE, false),
+}
+
+macro_rules! impl_vertex_attrib_type_matrix {
+    ($($ty:ty => $columns:expr),* $(,)?) => {
+        $(
+            unsafe impl VertexAttribType for $ty {
+                const LOCATIONS: gl::types::GLuint = $columns;
+                const COMPONENTS: gl::types::GLint = $columns;
+                const GL_TYPE: gl::types::GLenum = gl::FLOAT;
+                const INTEGER: bool = false;
+            }
+        )*
+    };
+}
+
+// column major, like GLSL's matN
+impl_vertex_attrib_type_matrix! {
+    Mat2 => 2,
+    Mat3 => 3,
+    Mat4 => 4,
+    [[f32; 2]; 2] => 2,
+    [[f32; 3]; 3] => 3,
+    [[f32; 4]; 4] => 4,
+}
+
+/// Size in bytes of one component of type `gl_type`
+fn component_size(gl_type: gl::types::GLenum) -> usize {
+    match gl_type {
+        gl::BYTE | gl::UNSIGNED_BYTE => 1,
+        gl::SHORT | gl::UNSIGNED_SHORT | gl::HALF_FLOAT => 2,
+        gl::DOUBLE => 8,
+        _ => 4,
+    }
+}
+
+/// Sets up the attribute pointers of `V` for the array buffer currently
+/// bound, on the vertex array currently bound. A `divisor` of 0 advances
+/// the attributes per vertex, and `n` advances them every `n` instances
+pub(crate) fn set_attribute_pointers<V>(divisor: gl::types::GLuint)
+where
+    V: Vertex,
+{
+    let stride = std::mem::size_of::<V>() as gl::types::GLsizei;
+    for attribute in V::ATTRIBUTES {
+        let column_size = attribute.components as usize * component_size(attribute.gl_type);
+        for column in 0..attribute.locations {
+            let location = attribute.location + column;
+            let offset = (attribute.offset + column as usize * column_size) as *const gl::types::GLvoid;
+            unsafe {
+                gl::EnableVertexAttribArray(location);
+                if attribute.integer {
+                    gl::VertexAttribIPointer(
+                        location,
+                        attribute.components,
+                        attribute.gl_type,
+                        stride,
+                        offset,
+                    );
+                } else {
+                    gl::VertexAttribPointer(
+                        location,
+                        attribute.components,
+                        attribute.gl_type,
+                        attribute.normalized as gl::types::GLboolean,
+                        stride,
+                        offset,
+                    );
+                }
+                gl::VertexAttribDivisor(location, divisor);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod test {
+    use super::*;
+    use crate::Vertex;
+
+    #[derive(Clone, Copy, Vertex)]
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct ColoredVertex {
+        position: Vec3,
+        #[vertex(normalized)]
+        color: [u8; 4],
+        #[vertex(location = 5)]
+        material: u32,
+        #[vertex(location = 6, integer)]
+        flags: [u8; 2],
+    }
+
+    #[test]
+    fn derived_attributes() {
+        assert_eq!(ColoredVertex::ATTRIBUTES, &[
+            VertexAttribute {
+                location: 0,
+                locations: 1,
+                components: 3,
+                gl_type: gl::FLOAT,
+                normalized: false,
+                integer: false,
+                offset: 0,
+            },
+            VertexAttribute {
+                location: 1,
+                locations: 1,
+                components: 4,
+                gl_type: gl::UNSIGNED_BYTE,
+                normalized: true,
+                integer: false,
+                offset: 12,
+            },
+            VertexAttribute {
+                location: 5,
+                locations: 1,
+                components: 1,
+                gl_type: gl::UNSIGNED_INT,
+                normalized: false,
+                integer: true,
+                offset: 16,
+            },
+            VertexAttribute {
+                location: 6,
+                locations: 1,
+                components: 2,
+                gl_type: gl::UNSIGNED_BYTE,
+                normalized: false,
+                integer: true,
+                offset: 20,
+            },
+        ]);
+    }
+
+    #[derive(Clone, Copy, Vertex)]
+    #[repr(C, align(16))]
+    #[allow(dead_code)]
+    struct Picking {
+        position: Vec3,
+        #[vertex(normalized)]
+        id: u32,
+    }
+
+    #[test]
+    fn derived_vertices_are_pod() {
+        fn assert_pod<T: crate::buffer::Pod>() {}
+        assert_pod::<ColoredVertex>();
+        assert_pod::<Picking>();
+    }
+
+    #[test]
+    fn normalized_integers_are_read_as_floats() {
+        let id = &Picking::ATTRIBUTES[1];
+        assert_eq!((id.gl_type, id.normalized, id.integer), (gl::UNSIGNED_INT, true, false));
+        assert_eq!(std::mem::align_of::<Picking>(), 16);
+    }
+
+    #[derive(Clone, Copy, Vertex)]
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct Instance {
+        #[vertex(location = 3)]
+        transform: Mat4,
+        color: [f32; 4],
+    }
+
+    #[test]
+    fn matrices_take_a_location_per_column() {
+        let attributes = Instance::ATTRIBUTES;
+        assert_eq!((attributes[0].location, attributes[0].locations), (3, 4));
+        assert_eq!((attributes[1].location, attributes[1].locations), (7, 1));
+        assert_eq!(attributes[1].offset, 64);
+    }
+}
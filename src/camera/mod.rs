@@ -104,8 +104,11 @@ impl Camera {
 	}
 
     pub fn screen_to_world(&self, pt: Point2f) -> Point2f {
-		let v = nalgebra_glm::Vec4::new(pt.x, pt.y, perspective_z(NEAR, FAR, -self.z()), 1_f32);
-		let world_pt = self.view_inverse() * self.projection_inverse() * v;
+		// unproject straight onto the world plane instead of going through
+		// projection_inverse, whose depth term loses most of the f32 precision
+		let half_extent = f32::tan(0.5_f32 * FOV_ANGLE) * self.z();
+		let v = nalgebra_glm::Vec4::new(pt.x * half_extent, pt.y * half_extent, -self.z(), 1_f32);
+		let world_pt = self.view_inverse() * v;
 		Point2f::new(world_pt.x, world_pt.y) / world_pt.w
     }
 
@@ -169,7 +172,7 @@ pub fn inverse_perspective(fov_angle: f32, near: f32, far: f32) -> nalgebra_glm:
 
 #[cfg(test)]
 mod test {
-    use crate::render_system::camera::*;
+    use crate::camera::*;
 
     use super::perspective;
 
@@ -1,4 +1,5 @@
 use gl;
+use crate::{buffer::Pod, PendingRead, Texture, TextureType};
 
 
 pub struct FrameBuffer {
@@ -33,6 +34,155 @@ impl FrameBuffer {
 	}
 }
 
+impl FrameBuffer {
+    /// Attaches `texture` at `attach_point`, e.g. `gl::COLOR_ATTACHMENT0`.
+    /// Unlike `Texture::attach_to_fbo` it doesn't matter which framebuffer
+    /// is bound, and the binding is left as it was
+    pub fn attach_texture<TTex, TData>(
+        &self,
+        texture: &mut Texture<TTex, TData>,
+        attach_point: gl::types::GLenum,
+    ) where
+        TTex: TextureType<TData>,
+        TData: Default + Clone,
+    {
+        self.with_draw_binding(|| texture.attach_to_fbo(attach_point));
+    }
+
+    /// Attaches `render_buffer` at `attach_point`, e.g.
+    /// `gl::DEPTH_STENCIL_ATTACHMENT`, leaving the binding as it was
+    pub fn attach_render_buffer(
+        &self,
+        render_buffer: &mut RenderBuffer,
+        attach_point: gl::types::GLenum,
+    ) {
+        self.with_draw_binding(|| render_buffer.attach_to_fbo(attach_point));
+    }
+
+    /// Runs `f` with this as the draw framebuffer, then rebinds the one that
+    /// was bound before. Attaching to or drawing into `gl::FRAMEBUFFER` only
+    /// goes through the draw binding, so the read binding is left alone
+    pub(crate) fn with_draw_binding(&self, f: impl FnOnce()) {
+        let mut current_fbo: gl::types::GLint = 0;
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut current_fbo);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.fbo);
+        }
+        f();
+        unsafe {
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, current_fbo as u32);
+        }
+    }
+
+    /// Starts copying a `width` by `height` rectangle of the color
+    /// `attachment` into a pixel buffer, in the pixel format of the texture
+    /// type `TTex`. The framebuffer binding is left as it was
+    pub fn read_async<TTex, TData>(
+        &self,
+        attachment: gl::types::GLenum,
+        x: i32,
+        y: i32,
+        width: usize,
+        height: usize,
+    ) -> PendingRead<TData>
+    where
+        TTex: TextureType<TData>,
+        TData: Pod,
+    {
+        let mut current_fbo: gl::types::GLint = 0;
+        unsafe {
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut current_fbo);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+            gl::ReadBuffer(attachment);
+        }
+        let read = PendingRead::new(width, height, || unsafe {
+            gl::ReadPixels(
+                x,
+                y,
+                width as i32,
+                height as i32,
+                TTex::TEXTURE_TYPE,
+                TTex::DATA_TYPE,
+                std::ptr::null_mut(),
+            );
+        });
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, current_fbo as u32);
+        }
+        read
+    }
+}
+
+/// Image storage that can only be rendered to, not sampled, which lets the
+/// driver pick the fastest layout. Usually used for the depth and stencil
+/// buffers of a framebuffer whose color is the only output read later
+pub struct RenderBuffer {
+    pub id: gl::types::GLuint,
+    pub width: gl::types::GLint,
+    pub height: gl::types::GLint,
+    pub internal_format: gl::types::GLenum,
+    pub attach_point: gl::types::GLenum,
+}
+
+impl RenderBuffer {
+    /// Allocates a render buffer with a sized internal format like
+    /// `gl::DEPTH_COMPONENT24`, `gl::DEPTH24_STENCIL8` or `gl::RGBA8`
+    pub fn new(internal_format: gl::types::GLenum, width: i32, height: i32) -> RenderBuffer {
+        Self::new_multisample(internal_format, width, height, 0)
+    }
+
+    /// Allocates a render buffer with `samples` samples per pixel, for
+    /// multisampled framebuffers. Every attachment of a framebuffer needs
+    /// the same number of samples
+    pub fn new_multisample(
+        internal_format: gl::types::GLenum,
+        width: i32,
+        height: i32,
+        samples: i32,
+    ) -> RenderBuffer {
+        let mut id: gl::types::GLuint = 0;
+        unsafe {
+            gl::GenRenderbuffers(1, &mut id);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, id);
+            gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, samples, internal_format, width, height);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+
+        RenderBuffer {
+            id,
+            width,
+            height,
+            internal_format,
+            attach_point: 0,
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindRenderbuffer(gl::RENDERBUFFER, self.id); }
+    }
+
+    pub fn unbind(&self) {
+        unsafe { gl::BindRenderbuffer(gl::RENDERBUFFER, 0); }
+    }
+
+    /// Attaches to the bound framebuffer at `attach_point`, e.g.
+    /// `gl::DEPTH_STENCIL_ATTACHMENT`, like `Texture::attach_to_fbo`
+    pub fn attach_to_fbo(&mut self, attach_point: gl::types::GLenum) {
+        unsafe {
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, attach_point, gl::RENDERBUFFER, self.id);
+        }
+        self.attach_point = attach_point;
+    }
+}
+
+impl Drop for RenderBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteRenderbuffers(1, &self.id);
+        }
+    }
+}
+
 pub fn save_currently_bound_framebuffer() -> gl::types::GLint {
 	let mut current_fbo: gl::types::GLint = 0;
 	unsafe {
@@ -47,6 +197,12 @@ pub fn restore_framebuffer(target: gl::types::GLint) {
 	}
 }
 
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Drop for FrameBuffer {
     fn drop(&mut self) {
 	unsafe {
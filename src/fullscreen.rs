@@ -0,0 +1,178 @@
+//! Shadertoy style fullscreen passes: a fragment shader body run over every
+//! pixel of the target, with the usual `iTime`, `iResolution`, `iMouse`
+//! and `iChannelN` uniforms filled in automatically
+
+use crate::{shader::FULLSCREEN_VERT, FrameBuffer, Program, TextureBinding, VertexArray};
+
+/// The per frame values a [`FullscreenPass`] hands to its shader
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameContext {
+    /// Seconds since the effect started, `iTime`
+    pub time: f32,
+    /// Seconds since the last frame, `iTimeDelta`
+    pub time_delta: f32,
+    /// Frames rendered so far, `iFrame`
+    pub frame: i32,
+    /// Size of the target in pixels, `iResolution.xy`
+    pub resolution: [f32; 2],
+    /// `xy` is the mouse position in pixels while a button is held, `zw`
+    /// where it was last clicked, as in Shadertoy. `iMouse`
+    pub mouse: [f32; 4],
+}
+
+impl FrameContext {
+    pub fn new(width: u32, height: u32) -> Self {
+        FrameContext {
+            resolution: [width as f32, height as f32],
+            ..Default::default()
+        }
+    }
+
+    /// Moves on to the next frame, `dt` seconds after the current one
+    pub fn advance(&mut self, dt: f32) {
+        self.time += dt;
+        self.time_delta = dt;
+        self.frame += 1;
+    }
+}
+
+/// Runs a fragment shader body over the whole viewport. The body has to
+/// define `void mainImage(out vec4 fragColor, in vec2 fragCoord)`, with
+/// `fragCoord` in pixels, and can read these uniforms:
+///
+/// ```glsl
+/// uniform vec3 iResolution;
+/// uniform float iTime;
+/// uniform float iTimeDelta;
+/// uniform int iFrame;
+/// uniform vec4 iMouse;
+/// uniform sampler2D iChannel0; // up to iChannel3
+/// ```
+///
+/// The viewport isn't changed, set it to the target's size beforehand.
+pub struct FullscreenPass {
+    program: Program,
+    vao: VertexArray,
+}
+
+impl FullscreenPass {
+    pub const MAX_CHANNELS: usize = 4;
+
+    pub fn new(body: &str) -> Result<FullscreenPass, String> {
+        let program = Program::from_src(FULLSCREEN_VERT, &fragment_source(body))?;
+
+        program.set_used();
+        for channel in 0..Self::MAX_CHANNELS {
+            program.set_uniform(&format!("iChannel{}", channel), &(channel as i32));
+        }
+
+        Ok(FullscreenPass {
+            program,
+            vao: VertexArray::new(),
+        })
+    }
+
+    /// The underlying program, for setting any extra uniforms the body uses
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// Renders into whatever framebuffer is currently bound, which is the
+    /// screen unless one has been bound. `channels[n]` is bound as
+    /// `iChannel<n>`
+    pub fn render(&self, ctx: &FrameContext, channels: &[&dyn TextureBinding]) {
+        assert!(
+            channels.len() <= Self::MAX_CHANNELS,
+            "a fullscreen pass takes at most {} channels",
+            Self::MAX_CHANNELS
+        );
+
+        self.program.set_used();
+        self.program.set_uniform("iResolution", &[ctx.resolution[0], ctx.resolution[1], 1.0]);
+        self.program.set_uniform("iTime", &ctx.time);
+        self.program.set_uniform("iTimeDelta", &ctx.time_delta);
+        self.program.set_uniform("iFrame", &ctx.frame);
+        self.program.set_uniform("iMouse", &ctx.mouse);
+
+        for (unit, channel) in channels.iter().enumerate() {
+            channel.attach_to_unit(unit as gl::types::GLuint);
+        }
+
+        self.vao.bind();
+        unsafe {
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        }
+        self.vao.unbind();
+    }
+
+    /// Renders into `target`, then rebinds the draw framebuffer that was
+    /// bound before. The read framebuffer binding isn't touched
+    pub fn render_to(&self, target: &FrameBuffer, ctx: &FrameContext, channels: &[&dyn TextureBinding]) {
+        target.with_draw_binding(|| self.render(ctx, channels));
+    }
+}
+
+fn fragment_source(body: &str) -> String {
+    format!(
+        "#version 330 core\n\
+         uniform vec3 iResolution;\n\
+         uniform float iTime;\n\
+         uniform float iTimeDelta;\n\
+         uniform int iFrame;\n\
+         uniform vec4 iMouse;\n\
+         uniform sampler2D iChannel0;\n\
+         uniform sampler2D iChannel1;\n\
+         uniform sampler2D iChannel2;\n\
+         uniform sampler2D iChannel3;\n\
+         out vec4 rgl_FragColor;\n\
+         #line 1\n\
+         {}\n\
+         void main()\n\
+         {{\n\
+         \x20   mainImage(rgl_FragColor, gl_FragCoord.xy);\n\
+         }}\n",
+        body
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PLASMA: &str = "void mainImage(out vec4 fragColor, in vec2 fragCoord)\n\
+                          {\n\
+                          \x20   vec2 uv = fragCoord / iResolution.xy;\n\
+                          \x20   vec3 tex = texture(iChannel0, uv).rgb;\n\
+                          \x20   fragColor = vec4(tex * (0.5 + 0.5 * sin(iTime + uv.x * 10.0)), 1.0);\n\
+                          }";
+
+    #[test]
+    fn body_lines_start_at_one() {
+        let src = fragment_source(PLASMA);
+        let (header, body) = src.split_once("#line 1\n").unwrap();
+        assert!(header.starts_with("#version 330 core\n"));
+        assert!(body.starts_with(PLASMA));
+        assert!(body.contains("mainImage(rgl_FragColor, gl_FragCoord.xy);"));
+    }
+
+    #[test]
+    fn advance_counts_frames() {
+        let mut ctx = FrameContext::new(640, 480);
+        ctx.advance(0.5);
+        ctx.advance(0.25);
+        assert_eq!(ctx.frame, 2);
+        assert_eq!(ctx.time, 0.75);
+        assert_eq!(ctx.time_delta, 0.25);
+        assert_eq!(ctx.resolution, [640.0, 480.0]);
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn generated_program_is_valid() {
+        crate::ProgramBuilder::new()
+            .vertex(FULLSCREEN_VERT)
+            .fragment(&fragment_source(PLASMA))
+            .validate()
+            .unwrap();
+    }
+}
@@ -1,26 +1,49 @@
+// lets the derive macros, which refer to `::render_gl`, be used in this crate
+extern crate self as render_gl;
+
 pub mod buffer;
 pub mod framebuffer;
+mod fullscreen;
 mod shader;
 pub mod text;
 pub mod texture;
 pub mod camera;
+mod material;
+mod mesh;
 mod rectangle;
+mod sync;
 
-pub use buffer::{ArrayBuffer, ElementArrayBuffer, VertexArray};
+pub use buffer::{
+    ArrayBuffer, DrawIndirectBuffer, ElementArrayBuffer, IndexType, PendingRead,
+    ShaderStorageBuffer, StreamBuffer, UniformBlock, UniformBuffer, VertexArray,
+};
 pub use framebuffer::*;
-pub use shader::{Program, Shader};
+pub use fullscreen::{FrameContext, FullscreenPass};
+pub use shader::{
+    memory_barrier, memory_barrier_by_region, ComputeProgram, PrimitivesWritten, Program,
+    ProgramBuilder, ProgramCache, ProgramVariants, Severity, Shader, ShaderDiagnostic,
+    TransformFeedback, Uniform, UniformStats, UniformValue, Uniforms, ATTRIB_COLOR,
+    ATTRIB_POSITION, ATTRIB_TEX_COORDS,
+};
+#[cfg(feature = "derive")]
+pub use render_gl_derive::{Uniforms, Vertex};
+#[cfg(feature = "validation")]
+pub use shader::{validate_shader, ShaderInterface, UniformDecl};
 pub use texture::*;
+pub use material::Material;
+pub use mesh::{Mesh, Topology};
 pub use rectangle::Rect;
+pub use sync::Fence;
 
 #[macro_export]
 macro_rules! gl_panic {
-    () => {
-        unsafe {
-            if gl::GetError() != gl::NO_ERROR {
-                panic!("OpenGL error!");
-            }
+    () => {{
+        #[allow(unused_unsafe)]
+        let err = unsafe { gl::GetError() };
+        if err != gl::NO_ERROR {
+            panic!("OpenGL error!");
         }
-    };
+    }};
 }
 
 #[macro_export]
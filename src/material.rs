@@ -0,0 +1,77 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{Program, TextureBinding, UniformValue};
+
+/// Ties a program to the uniform values and textures it's drawn with, so
+/// everything can be bound with a single `apply()` before drawing.
+///
+/// Textures are given texture units in the order they're first added, and
+/// their sampler uniforms are pointed at those units.
+pub struct Material {
+    program: Rc<Program>,
+    uniforms: HashMap<String, UniformValue>,
+    textures: Vec<(String, Rc<dyn TextureBinding>)>,
+}
+
+impl Material {
+    pub fn new(program: Rc<Program>) -> Self {
+        Material {
+            program,
+            uniforms: HashMap::new(),
+            textures: Vec::new(),
+        }
+    }
+
+    pub fn program(&self) -> &Rc<Program> {
+        &self.program
+    }
+
+    pub fn set<V>(&mut self, name: &str, value: V) -> &mut Self
+    where
+        V: Into<UniformValue>,
+    {
+        self.uniforms.insert(name.to_string(), value.into());
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&UniformValue> {
+        self.uniforms.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<UniformValue> {
+        self.uniforms.remove(name)
+    }
+
+    /// Binds `texture` to the sampler uniform `name`. Replacing a texture
+    /// keeps the texture unit it was given
+    pub fn set_texture(&mut self, name: &str, texture: Rc<dyn TextureBinding>) -> &mut Self {
+        match self.textures.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) => *existing = texture,
+            None => self.textures.push((name.to_string(), texture)),
+        }
+        self
+    }
+
+    /// The texture unit the sampler uniform `name` is bound to
+    pub fn texture_unit(&self, name: &str) -> Option<gl::types::GLuint> {
+        self.textures
+            .iter()
+            .position(|(n, _)| n == name)
+            .map(|unit| unit as gl::types::GLuint)
+    }
+
+    /// Makes the program current, attaches every texture to its unit and
+    /// sets every uniform
+    pub fn apply(&self) {
+        self.program.set_used();
+
+        for (unit, (name, texture)) in self.textures.iter().enumerate() {
+            texture.attach_to_unit(unit as gl::types::GLuint);
+            self.program.set_uniform(name, &(unit as i32));
+        }
+
+        for (name, value) in &self.uniforms {
+            self.program.set_uniform(name, value);
+        }
+    }
+}
@@ -0,0 +1,409 @@
+use std::ops::Range;
+
+use crate::buffer::{DrawElementsIndirectCommand, DrawIndirectBuffer, IndexType, IndirectCommand, Vertex};
+use crate::{ArrayBuffer, ElementArrayBuffer, VertexArray};
+
+/// How a mesh's vertices are assembled into primitives
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology {
+    Points,
+    Lines,
+    LineStrip,
+    LineLoop,
+    Triangles,
+    TriangleStrip,
+    TriangleFan,
+}
+
+impl Topology {
+    pub fn gl_mode(self) -> gl::types::GLenum {
+        match self {
+            Topology::Points => gl::POINTS,
+            Topology::Lines => gl::LINES,
+            Topology::LineStrip => gl::LINE_STRIP,
+            Topology::LineLoop => gl::LINE_LOOP,
+            Topology::Triangles => gl::TRIANGLES,
+            Topology::TriangleStrip => gl::TRIANGLE_STRIP,
+            Topology::TriangleFan => gl::TRIANGLE_FAN,
+        }
+    }
+}
+
+/// A vertex array together with the vertex and (optional) index buffers it
+/// draws from. Indices are checked against the vertex count when the mesh
+/// is made or updated and draw ranges against the index count, so drawing
+/// can't read past the end of either buffer. The buffers are only written
+/// through the mesh for the same reason.
+///
+/// Ranges (of indices, or of vertices for meshes without indices) can be
+/// registered as sub-meshes and drawn on their own, e.g. one per material.
+pub struct Mesh<V, I = u32>
+where
+    V: Vertex,
+    I: IndexType,
+{
+    vao: VertexArray,
+    vertices: ArrayBuffer<V>,
+    indices: Option<ElementArrayBuffer<I>>,
+    topology: Topology,
+    sub_meshes: Vec<Range<usize>>,
+    /// Largest index that isn't `I::RESTART`, which the vertices have to
+    /// cover
+    max_index: Option<usize>,
+    primitive_restart: bool,
+    /// Whether the indices contain `I::RESTART`, which is only in range
+    /// with primitive restart on
+    restart_indices: bool,
+}
+
+impl<V, I> Mesh<V, I>
+where
+    V: Vertex,
+    I: IndexType,
+{
+    /// A mesh drawn straight from `vertices`, in order
+    pub fn new(topology: Topology, vertices: &[V]) -> Self {
+        let vao = VertexArray::new();
+        let vertex_buffer = ArrayBuffer::new();
+        vertex_buffer.static_draw_data(vertices);
+        vao.configure_attributes::<V>(&vertex_buffer);
+        vao.unbind();
+
+        Mesh {
+            vao,
+            vertices: vertex_buffer,
+            indices: None,
+            topology,
+            sub_meshes: Vec::new(),
+            max_index: None,
+            primitive_restart: false,
+            restart_indices: false,
+        }
+    }
+
+    /// A mesh drawn by indexing into `vertices`. Fails if an index is out of
+    /// range, not counting `I::RESTART`. Indices containing `I::RESTART`
+    /// turn primitive restart on, since they can't be drawn without it
+    pub fn new_indexed(topology: Topology, vertices: &[V], indices: &[I]) -> Result<Self, String> {
+        let scan = check_indices(indices, vertices.len())?;
+
+        let mut mesh = Self::new(topology, vertices);
+        let index_buffer = ElementArrayBuffer::new();
+        index_buffer.static_draw_data(indices);
+        // binding with the vertex array bound attaches the index buffer to it
+        mesh.vao.bind();
+        index_buffer.bind();
+        mesh.vao.unbind();
+        mesh.indices = Some(index_buffer);
+        mesh.set_index_scan(scan);
+
+        Ok(mesh)
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// The vertex array, for adding per-instance attributes
+    pub fn vertex_array(&self) -> &VertexArray {
+        &self.vao
+    }
+
+    /// Replaces the vertices, failing if there are too few for the indices
+    /// or, for meshes without indices, the sub-meshes
+    pub fn update_vertices(&mut self, vertices: &[V]) -> Result<(), String> {
+        if let Some(max) = self.max_index.filter(|&max| max >= vertices.len()) {
+            return Err(format!(
+                "Index {} is out of range for a mesh with {} vertices",
+                max,
+                vertices.len()
+            ));
+        }
+        if self.indices.is_none() {
+            check_sub_meshes(&self.sub_meshes, vertices.len())?;
+        }
+
+        self.vertices.update_data(vertices);
+        Ok(())
+    }
+
+    /// Replaces the indices of a mesh made with `new_indexed`, checking them
+    /// like `new_indexed` does. Fails if the sub-meshes no longer fit
+    pub fn update_indices(&mut self, indices: &[I]) -> Result<(), String> {
+        let index_buffer = self
+            .indices
+            .as_ref()
+            .ok_or_else(|| "Only meshes made with indices can update them".to_string())?;
+        let scan = check_indices(indices, self.vertices.len())?;
+        check_sub_meshes(&self.sub_meshes, indices.len())?;
+
+        // keeps the buffer's name, so the vertex array still points at it
+        index_buffer.update_data(indices);
+        self.set_index_scan(scan);
+        Ok(())
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Number of indices, or vertices for meshes without indices, that a
+    /// full draw goes through
+    pub fn element_count(&self) -> usize {
+        match &self.indices {
+            Some(indices) => indices.len(),
+            None => self.vertices.len(),
+        }
+    }
+
+    pub fn primitive_restart(&self) -> bool {
+        self.primitive_restart
+    }
+
+    /// Whether `I::RESTART` in the indices starts a new strip or fan. It
+    /// can't be turned off while the indices contain `I::RESTART`, which
+    /// would then be out of range
+    pub fn set_primitive_restart(&mut self, enabled: bool) -> Result<(), String> {
+        if !enabled && self.restart_indices {
+            return Err(
+                "Primitive restart can't be turned off, the mesh's indices contain the restart index"
+                    .to_string(),
+            );
+        }
+        self.primitive_restart = enabled;
+        Ok(())
+    }
+
+    /// Registers a range of elements that can be drawn with
+    /// `draw_sub_mesh`, returning its index
+    pub fn add_sub_mesh(&mut self, range: Range<usize>) -> Result<usize, String> {
+        self.check_range(&range)?;
+        self.sub_meshes.push(range);
+        Ok(self.sub_meshes.len() - 1)
+    }
+
+    pub fn sub_meshes(&self) -> &[Range<usize>] {
+        &self.sub_meshes
+    }
+
+    pub fn draw(&self) {
+        self.draw_instanced_range(0..self.element_count(), 1);
+    }
+
+    /// Draws a sub-mesh registered with `add_sub_mesh`
+    pub fn draw_sub_mesh(&self, sub_mesh: usize) {
+        self.draw_instanced_range(self.sub_meshes[sub_mesh].clone(), 1);
+    }
+
+    /// Draws a range of elements, panicking if it's out of bounds
+    pub fn draw_range(&self, range: Range<usize>) {
+        self.draw_instanced_range(range, 1);
+    }
+
+    pub fn draw_instanced(&self, instance_count: usize) {
+        self.draw_instanced_range(0..self.element_count(), instance_count);
+    }
+
+    /// Draws `instance_count` instances of a range of elements, panicking
+    /// if it's out of bounds
+    pub fn draw_instanced_range(&self, range: Range<usize>, instance_count: usize) {
+        if let Err(err) = self.check_range(&range) {
+            panic!("{}", err);
+        }
+
+        let mode = self.topology.gl_mode();
+        let count = range.len() as gl::types::GLsizei;
+        self.vao.bind();
+        unsafe {
+            match &self.indices {
+                Some(_) => {
+                    let offset = range.start * std::mem::size_of::<I>();
+                    self.with_primitive_restart(|| {
+                        gl::DrawElementsInstanced(
+                            mode,
+                            count,
+                            I::GL_TYPE,
+                            offset as *const gl::types::GLvoid,
+                            instance_count as gl::types::GLsizei,
+                        );
+                    });
+                }
+                None => {
+                    gl::DrawArraysInstanced(
+                        mode,
+                        range.start as gl::types::GLint,
+                        count,
+                        instance_count as gl::types::GLsizei,
+                    );
+                }
+            }
+        }
+        self.vao.unbind();
+    }
+
+    /// The indirect command drawing `instance_count` instances of a sub-mesh,
+    /// for filling a draw indirect buffer on the CPU
+    pub fn sub_mesh_command(&self, sub_mesh: usize, instance_count: u32) -> DrawElementsIndirectCommand {
+        let range = &self.sub_meshes[sub_mesh];
+        DrawElementsIndirectCommand {
+            count: range.len() as u32,
+            instance_count,
+            first_index: range.start as u32,
+            base_vertex: 0,
+            base_instance: 0,
+        }
+    }
+
+    /// Issues every command in `commands` with a single call. Indexed
+    /// commands need a mesh with indices and draw from them, with
+    /// `first_index` and `base_vertex` picking out the packed sub-mesh.
+    /// The commands aren't checked against the mesh's buffers
+    pub fn draw_indirect<C>(&self, commands: &DrawIndirectBuffer<C>)
+    where
+        C: IndirectCommand,
+    {
+        let mode = self.topology.gl_mode();
+        commands.bind();
+        self.vao.bind();
+        unsafe {
+            if C::INDEXED {
+                assert!(
+                    self.indices.is_some(),
+                    "Indexed indirect draws need a mesh with indices"
+                );
+                self.with_primitive_restart(|| {
+                    gl::MultiDrawElementsIndirect(
+                        mode,
+                        I::GL_TYPE,
+                        std::ptr::null(),
+                        commands.len() as gl::types::GLsizei,
+                        0,
+                    );
+                });
+            } else {
+                gl::MultiDrawArraysIndirect(
+                    mode,
+                    std::ptr::null(),
+                    commands.len() as gl::types::GLsizei,
+                    0,
+                );
+            }
+        }
+        self.vao.unbind();
+        commands.unbind();
+    }
+
+    /// Runs an indexed draw with primitive restart enabled if the mesh uses
+    /// it. It's turned off again afterwards rather than queried and
+    /// restored, since a `glGet` per draw can stall threaded drivers, so
+    /// other draws see GL's default of off
+    unsafe fn with_primitive_restart<F>(&self, draw: F)
+    where
+        F: FnOnce(),
+    {
+        if self.primitive_restart {
+            gl::Enable(gl::PRIMITIVE_RESTART_FIXED_INDEX);
+        }
+        draw();
+        if self.primitive_restart {
+            gl::Disable(gl::PRIMITIVE_RESTART_FIXED_INDEX);
+        }
+    }
+
+    fn set_index_scan(&mut self, scan: IndexScan) {
+        self.max_index = scan.max_index;
+        self.restart_indices = scan.restart;
+        if scan.restart {
+            self.primitive_restart = true;
+        }
+    }
+
+    fn check_range(&self, range: &Range<usize>) -> Result<(), String> {
+        if range.start > range.end || range.end > self.element_count() {
+            return Err(format!(
+                "Range {:?} is out of bounds for a mesh with {} elements",
+                range,
+                self.element_count()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What a mesh needs to know about its indices to check later updates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct IndexScan {
+    /// Largest in-range index
+    max_index: Option<usize>,
+    /// Whether `I::RESTART` was found out of range, so the mesh needs
+    /// primitive restart
+    restart: bool,
+}
+
+/// Checks the indices are within `vertex_count`, apart from `I::RESTART`
+fn check_indices<I>(indices: &[I], vertex_count: usize) -> Result<IndexScan, String>
+where
+    I: IndexType,
+{
+    let restart = I::RESTART.to_usize();
+    let mut scan = IndexScan {
+        max_index: None,
+        restart: false,
+    };
+    for i in indices.iter().map(|i| i.to_usize()) {
+        if i < vertex_count {
+            scan.max_index = scan.max_index.max(Some(i));
+        } else if i == restart {
+            scan.restart = true;
+        } else {
+            return Err(format!(
+                "Index {} is out of range for a mesh with {} vertices",
+                i, vertex_count
+            ));
+        }
+    }
+    Ok(scan)
+}
+
+fn check_sub_meshes(sub_meshes: &[Range<usize>], element_count: usize) -> Result<(), String> {
+    match sub_meshes.iter().find(|range| range.end > element_count) {
+        Some(range) => Err(format!(
+            "Sub-mesh {:?} would be out of bounds for a mesh with {} elements",
+            range, element_count
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn indices_within_vertex_count() {
+        assert_eq!(
+            check_indices(&[0_u16, 1, 2, 2, 1, 3], 4),
+            Ok(IndexScan { max_index: Some(3), restart: false })
+        );
+        assert!(check_indices(&[0_u16, 1, 4], 4).is_err());
+    }
+
+    #[test]
+    fn restart_index_is_found() {
+        assert_eq!(
+            check_indices(&[0_u8, 1, 2, u8::MAX, 1, 2, 3], 4),
+            Ok(IndexScan { max_index: Some(3), restart: true })
+        );
+        // an ordinary index when the mesh is big enough to need it
+        assert_eq!(
+            check_indices(&[0_u8, 1, u8::MAX], 256),
+            Ok(IndexScan { max_index: Some(255), restart: false })
+        );
+    }
+
+    #[test]
+    fn sub_meshes_within_element_count() {
+        assert!(check_sub_meshes(&[0..3, 3..6], 6).is_ok());
+        assert!(check_sub_meshes(&[0..3, 3..6], 5).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+#version 330 core
+out vec2 TexCoords;
+
+// a single triangle covering the screen, generated from gl_VertexID so no
+// vertex buffer is needed
+void main()
+{
+    vec2 corner = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    TexCoords = corner;
+    gl_Position = vec4(corner * 2.0 - 1.0, 0.0, 1.0);
+}
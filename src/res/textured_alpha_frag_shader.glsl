@@ -0,0 +1,11 @@
+#version 330 core
+in vec2 TexCoords;
+out vec4 fragColor;
+
+uniform sampler2D tex;
+uniform vec4 color;
+
+void main()
+{
+    fragColor = vec4(color.rgb, color.a * texture(tex, TexCoords).r);
+}
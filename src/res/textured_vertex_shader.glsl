@@ -0,0 +1,12 @@
+#version 330 core
+layout (location = 0) in vec3 position;
+layout (location = 2) in vec2 texCoords;
+out vec2 TexCoords;
+
+uniform mat4 mvp;
+
+void main()
+{
+    gl_Position = mvp * vec4(position, 1.0);
+    TexCoords = texCoords;
+}
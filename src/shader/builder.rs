@@ -0,0 +1,200 @@
+use std::ffi::CString;
+
+use super::{Program, Shader};
+
+/// Collects the source of every stage that makes up a program, then compiles
+/// and links them in one go. Stages are linked in pipeline order regardless of
+/// the order they were added in
+#[derive(Clone, Default)]
+pub struct ProgramBuilder {
+    stages: Vec<(gl::types::GLenum, String)>,
+    feedback: Option<(Vec<String>, gl::types::GLenum)>,
+}
+
+impl ProgramBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn vertex(self, src: &str) -> Self {
+        self.stage(gl::VERTEX_SHADER, src)
+    }
+
+    pub fn tess_control(self, src: &str) -> Self {
+        self.stage(gl::TESS_CONTROL_SHADER, src)
+    }
+
+    pub fn tess_evaluation(self, src: &str) -> Self {
+        self.stage(gl::TESS_EVALUATION_SHADER, src)
+    }
+
+    pub fn geometry(self, src: &str) -> Self {
+        self.stage(gl::GEOMETRY_SHADER, src)
+    }
+
+    pub fn fragment(self, src: &str) -> Self {
+        self.stage(gl::FRAGMENT_SHADER, src)
+    }
+
+    /// Sets the source for the given stage, replacing any source previously
+    /// given for that stage
+    pub fn stage(mut self, kind: gl::types::GLenum, src: &str) -> Self {
+        self.stages.retain(|(k, _)| *k != kind);
+        self.stages.push((kind, src.to_string()));
+        self.stages.sort_by_key(|(k, _)| stage_order(*k));
+        self
+    }
+
+    /// Captures the outputs named in `varyings` from the last stage before
+    /// the fragment shader during transform feedback. `buffer_mode` is
+    /// `gl::INTERLEAVED_ATTRIBS` to write them all into one buffer, or
+    /// `gl::SEPARATE_ATTRIBS` to write each into the buffer bound at its
+    /// index
+    pub fn transform_feedback(mut self, varyings: &[&str], buffer_mode: gl::types::GLenum) -> Self {
+        let varyings = varyings.iter().map(|v| v.to_string()).collect();
+        self.feedback = Some((varyings, buffer_mode));
+        self
+    }
+
+    pub fn build(&self) -> Result<Program, String> {
+        let varyings = self.feedback_varyings()?;
+        Program::link(&self.compile()?, |id| self.before_link(id, &varyings))
+    }
+
+    /// Like [`build`](Self::build), but hints to the driver that the
+    /// program's binary is going to be retrieved
+    pub(crate) fn build_retrievable(&self) -> Result<Program, String> {
+        let varyings = self.feedback_varyings()?;
+        Program::link(&self.compile()?, |id| unsafe {
+            gl::ProgramParameteri(id, gl::PROGRAM_BINARY_RETRIEVABLE_HINT, gl::TRUE as i32);
+            self.before_link(id, &varyings);
+        })
+    }
+
+    /// Everything that goes into the linked program, for keying caches
+    pub(crate) fn stages(&self) -> &[(gl::types::GLenum, String)] {
+        &self.stages
+    }
+
+    /// The transform feedback varyings and buffer mode, which also go into
+    /// the linked program
+    pub(crate) fn feedback(&self) -> Option<(&[String], gl::types::GLenum)> {
+        self.feedback
+            .as_ref()
+            .map(|(varyings, mode)| (varyings.as_slice(), *mode))
+    }
+
+    fn feedback_varyings(&self) -> Result<Vec<CString>, String> {
+        match &self.feedback {
+            Some((varyings, _)) => varyings
+                .iter()
+                .map(|v| {
+                    CString::new(v.as_str())
+                        .map_err(|_| format!("Transform feedback varying `{}` contains a nul byte", v))
+                })
+                .collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Varyings have to be declared before linking
+    fn before_link(&self, id: gl::types::GLuint, varyings: &[CString]) {
+        if let Some((_, buffer_mode)) = &self.feedback {
+            let ptrs: Vec<*const gl::types::GLchar> = varyings.iter().map(|v| v.as_ptr()).collect();
+            unsafe {
+                gl::TransformFeedbackVaryings(
+                    id,
+                    ptrs.len() as gl::types::GLsizei,
+                    ptrs.as_ptr(),
+                    *buffer_mode,
+                );
+            }
+        }
+    }
+
+    fn compile(&self) -> Result<Vec<Shader>, String> {
+        if !self.stages.iter().any(|(k, _)| *k == gl::VERTEX_SHADER) {
+            return Err("Program needs at least a vertex shader".to_string());
+        }
+
+        let mut shaders = Vec::with_capacity(self.stages.len());
+        for (kind, src) in &self.stages {
+            let c_src = CString::new(src.as_str())
+                .map_err(|_| format!("{} shader source contains a nul byte", stage_name(*kind)))?;
+            let shader = Shader::from_source(&c_src, *kind)
+                .map_err(|err| format!("Error compiling {} shader: {}", stage_name(*kind), err))?;
+            shaders.push(shader);
+        }
+
+        Ok(shaders)
+    }
+}
+
+fn stage_order(kind: gl::types::GLenum) -> u32 {
+    match kind {
+        gl::VERTEX_SHADER => 0,
+        gl::TESS_CONTROL_SHADER => 1,
+        gl::TESS_EVALUATION_SHADER => 2,
+        gl::GEOMETRY_SHADER => 3,
+        gl::FRAGMENT_SHADER => 4,
+        _ => 5,
+    }
+}
+
+pub(crate) fn stage_name(kind: gl::types::GLenum) -> &'static str {
+    match kind {
+        gl::VERTEX_SHADER => "vertex",
+        gl::TESS_CONTROL_SHADER => "tessellation control",
+        gl::TESS_EVALUATION_SHADER => "tessellation evaluation",
+        gl::GEOMETRY_SHADER => "geometry",
+        gl::FRAGMENT_SHADER => "fragment",
+        gl::COMPUTE_SHADER => "compute",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn kinds(builder: &ProgramBuilder) -> Vec<gl::types::GLenum> {
+        builder.stages().iter().map(|(kind, _)| *kind).collect()
+    }
+
+    #[test]
+    fn stages_are_kept_in_pipeline_order() {
+        let builder = ProgramBuilder::new()
+            .fragment("frag")
+            .geometry("geom")
+            .tess_evaluation("tes")
+            .vertex("vert")
+            .tess_control("tcs");
+        assert_eq!(
+            kinds(&builder),
+            [
+                gl::VERTEX_SHADER,
+                gl::TESS_CONTROL_SHADER,
+                gl::TESS_EVALUATION_SHADER,
+                gl::GEOMETRY_SHADER,
+                gl::FRAGMENT_SHADER,
+            ]
+        );
+    }
+
+    #[test]
+    fn stage_replaces_the_same_kind() {
+        let builder = ProgramBuilder::new()
+            .vertex("first")
+            .fragment("frag")
+            .stage(gl::VERTEX_SHADER, "second");
+        assert_eq!(kinds(&builder), [gl::VERTEX_SHADER, gl::FRAGMENT_SHADER]);
+        assert_eq!(builder.stages()[0].1, "second");
+    }
+
+    #[test]
+    fn vertex_shader_is_required() {
+        // fails before anything is compiled, so no context is needed
+        let err = ProgramBuilder::new().fragment("frag").build().err();
+        assert_eq!(err.as_deref(), Some("Program needs at least a vertex shader"));
+    }
+}
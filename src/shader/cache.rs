@@ -0,0 +1,199 @@
+//! An on-disk cache of linked program binaries, so programs only have to
+//! be compiled from source the first time they're built on a machine
+
+use std::{
+    ffi::CStr,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::{Program, ProgramBuilder};
+
+const MAGIC: &[u8; 4] = b"RGLP";
+
+/// Stores program binaries in a directory chosen by the user, keyed by a
+/// hash of the program's sources and the driver that linked it. Binaries
+/// that the driver rejects are rebuilt from source and replaced.
+pub struct ProgramCache {
+    dir: PathBuf,
+    driver: String,
+}
+
+impl ProgramCache {
+    /// Needs a current GL context, since the driver is part of the key
+    pub fn new<P: AsRef<Path>>(dir: P) -> std::io::Result<ProgramCache> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(ProgramCache {
+            dir: dir.as_ref().to_path_buf(),
+            driver: driver_description(),
+        })
+    }
+
+    /// Loads the program from the cache if possible, otherwise builds it
+    /// from source and stores its binary for next time. Failing to write
+    /// the cache isn't an error, the program is still returned
+    pub fn build(&self, builder: &ProgramBuilder) -> Result<Program, String> {
+        let path = self.path_for(builder);
+
+        if let Ok(contents) = fs::read(&path) {
+            let program = decode(&contents)
+                .and_then(|(format, binary)| Program::from_binary(format, binary).ok());
+            if let Some(program) = program {
+                return Ok(program);
+            }
+            let _ = fs::remove_file(&path);
+        }
+
+        let program = builder.build_retrievable()?;
+        if let Some((format, binary)) = program.binary() {
+            let _ = write_atomic(&path, &encode(format, &binary));
+        }
+
+        Ok(program)
+    }
+
+    /// Removes every binary from the cache directory
+    pub fn clear(&self) -> std::io::Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "bin" || ext == "tmp") {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn path_for(&self, builder: &ProgramBuilder) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.bin", cache_key(&self.driver, builder)))
+    }
+}
+
+fn driver_description() -> String {
+    [gl::VENDOR, gl::RENDERER, gl::VERSION]
+        .iter()
+        .map(|name| unsafe {
+            let ptr = gl::GetString(*name);
+            if ptr.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(ptr as *const _).to_string_lossy().into_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes to a temporary file next to `path` and renames it into place, so
+/// another process reading the cache never sees a half written binary
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    let result = fs::write(&tmp, contents).and_then(|_| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// FNV-1a, used instead of `DefaultHasher` since the key has to stay the
+/// same across builds of the program
+fn cache_key(driver: &str, builder: &ProgramBuilder) -> u64 {
+    let mut hash = Fnv1a::new();
+    hash.write(driver.as_bytes());
+    for (kind, src) in builder.stages() {
+        hash.write(&kind.to_le_bytes());
+        hash.write(&(src.len() as u64).to_le_bytes());
+        hash.write(src.as_bytes());
+    }
+    if let Some((varyings, buffer_mode)) = builder.feedback() {
+        hash.write(&buffer_mode.to_le_bytes());
+        for varying in varyings {
+            hash.write(&(varying.len() as u64).to_le_bytes());
+            hash.write(varying.as_bytes());
+        }
+    }
+    hash.finish()
+}
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn encode(format: gl::types::GLenum, binary: &[u8]) -> Vec<u8> {
+    let mut contents = Vec::with_capacity(MAGIC.len() + 4 + binary.len());
+    contents.extend_from_slice(MAGIC);
+    contents.extend_from_slice(&format.to_le_bytes());
+    contents.extend_from_slice(binary);
+    contents
+}
+
+fn decode(contents: &[u8]) -> Option<(gl::types::GLenum, &[u8])> {
+    let rest = contents.strip_prefix(MAGIC)?;
+    if rest.len() <= 4 {
+        return None;
+    }
+    let (format, binary) = rest.split_at(4);
+    Some((u32::from_le_bytes(format.try_into().ok()?), binary))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn key_depends_on_sources_and_driver() {
+        let a = ProgramBuilder::new().vertex("void main() {}").fragment("void main() {}");
+        let b = ProgramBuilder::new().vertex("void main() {}").fragment("void main() { }");
+
+        assert_eq!(cache_key("driver", &a), cache_key("driver", &a.clone()));
+        assert_ne!(cache_key("driver", &a), cache_key("driver", &b));
+        assert_ne!(cache_key("driver", &a), cache_key("other driver", &a));
+    }
+
+    #[test]
+    fn key_depends_on_feedback_varyings() {
+        let a = ProgramBuilder::new().vertex("void main() {}");
+        let b = a.clone().transform_feedback(&["position"], gl::INTERLEAVED_ATTRIBS);
+        let c = a.clone().transform_feedback(&["position"], gl::SEPARATE_ATTRIBS);
+
+        assert_ne!(cache_key("driver", &a), cache_key("driver", &b));
+        assert_ne!(cache_key("driver", &b), cache_key("driver", &c));
+    }
+
+    #[test]
+    fn write_atomic_replaces_the_file() {
+        let dir = std::env::temp_dir().join(format!("render_gl_cache_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("0123456789abcdef.bin");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let contents = encode(0x8741, &[1, 2, 3, 4, 5]);
+        assert_eq!(decode(&contents), Some((0x8741, &[1_u8, 2, 3, 4, 5][..])));
+        assert_eq!(decode(b"RGLP\x01\x00"), None);
+        assert_eq!(decode(b"nope"), None);
+    }
+}
@@ -0,0 +1,67 @@
+use std::{ffi::CString, ops::Deref};
+
+use super::{builder::stage_name, Program, Shader};
+
+/// A program made of a single compute shader. It derefs to [`Program`] so
+/// uniforms are set the same way as for any other program
+pub struct ComputeProgram {
+    program: Program,
+}
+
+impl ComputeProgram {
+    pub fn from_src(src: &str) -> Result<ComputeProgram, String> {
+        let c_src = CString::new(src)
+            .map_err(|_| "compute shader source contains a nul byte".to_string())?;
+        let shader = Shader::from_compute_source(&c_src).map_err(|err| {
+            format!("Error compiling {} shader: {}", stage_name(gl::COMPUTE_SHADER), err)
+        })?;
+
+        Ok(ComputeProgram {
+            program: Program::from_shaders(&[shader])?,
+        })
+    }
+
+    /// The local work group size declared in the shader with `layout(local_size_x = ...)`
+    pub fn work_group_size(&self) -> [i32; 3] {
+        let mut size = [0_i32; 3];
+        unsafe {
+            gl::GetProgramiv(self.program.id, gl::COMPUTE_WORK_GROUP_SIZE, size.as_mut_ptr());
+        }
+        size
+    }
+
+    /// Makes this the current program and launches `x * y * z` work groups.
+    /// Results written to buffers or images aren't guaranteed to be visible
+    /// to later commands until a matching [`memory_barrier`] has been issued
+    pub fn dispatch(&self, x: u32, y: u32, z: u32) {
+        self.program.set_used();
+        unsafe {
+            gl::DispatchCompute(x, y, z);
+        }
+    }
+}
+
+impl Deref for ComputeProgram {
+    type Target = Program;
+
+    fn deref(&self) -> &Program {
+        &self.program
+    }
+}
+
+/// Orders memory accesses made by shaders before this call against the
+/// accesses after it, e.g. `gl::SHADER_STORAGE_BARRIER_BIT` before reading
+/// back a buffer a compute shader wrote to
+pub fn memory_barrier(barriers: gl::types::GLbitfield) {
+    unsafe {
+        gl::MemoryBarrier(barriers);
+    }
+}
+
+/// Like [`memory_barrier`], but only orders accesses within the same
+/// framebuffer region, which is cheaper when that's all that's needed
+pub fn memory_barrier_by_region(barriers: gl::types::GLbitfield) {
+    unsafe {
+        gl::MemoryBarrierByRegion(barriers);
+    }
+}
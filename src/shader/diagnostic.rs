@@ -0,0 +1,167 @@
+//! Structured compiler diagnostics, shared by the driver's info log and the
+//! offline validator so the two can be reported and compared the same way
+
+use std::fmt;
+
+use super::builder::stage_name;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderDiagnostic {
+    /// The stage the diagnostic is for, e.g. `gl::FRAGMENT_SHADER`
+    pub stage: gl::types::GLenum,
+    pub severity: Severity,
+    /// 1 based line in the source that was handed to the compiler
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+impl ShaderDiagnostic {
+    pub fn error(stage: gl::types::GLenum, line: Option<u32>, message: String) -> Self {
+        ShaderDiagnostic {
+            stage,
+            severity: Severity::Error,
+            line,
+            message,
+        }
+    }
+
+    /// Splits a shader info log, as returned in the `Err` of
+    /// [`Shader::from_source`](super::Shader::from_source), into diagnostics.
+    /// Understands the Mesa (`0:12(5): error: ...`), NVIDIA
+    /// (`0(12) : error C0000: ...`) and AMD/Intel (`ERROR: 0:12: ...`)
+    /// formats; any other line is kept as an error without a line number
+    pub fn parse_info_log(stage: gl::types::GLenum, log: &str) -> Vec<ShaderDiagnostic> {
+        log.lines()
+            .filter_map(|line| parse_log_line(stage, line))
+            .collect()
+    }
+}
+
+impl fmt::Display for ShaderDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match self.line {
+            Some(line) => write!(f, "{} shader:{}: {}: {}", stage_name(self.stage), line, severity, self.message),
+            None => write!(f, "{} shader: {}: {}", stage_name(self.stage), severity, self.message),
+        }
+    }
+}
+
+fn parse_log_line(stage: gl::types::GLenum, line: &str) -> Option<ShaderDiagnostic> {
+    let line = line.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    if line.is_empty() {
+        return None;
+    }
+
+    let (mut severity, rest) = if let Some(rest) = line.strip_prefix("ERROR:") {
+        (Some(Severity::Error), rest)
+    } else if let Some(rest) = line.strip_prefix("WARNING:") {
+        (Some(Severity::Warning), rest)
+    } else {
+        (None, line)
+    };
+
+    let (line_number, mut rest) = match parse_location(rest) {
+        Some((line_number, rest)) => (Some(line_number), rest),
+        None => (None, rest),
+    };
+
+    if severity.is_none() {
+        let trimmed = rest.trim_start();
+        for (word, found) in [("error", Severity::Error), ("warning", Severity::Warning)] {
+            if trimmed.starts_with(word) {
+                severity = Some(found);
+                // skips vendor codes like NVIDIA's "error C1008:"
+                rest = trimmed.split_once(':').map_or("", |(_, msg)| msg);
+                break;
+            }
+        }
+    }
+
+    Some(ShaderDiagnostic {
+        stage,
+        severity: severity.unwrap_or(Severity::Error),
+        line: line_number,
+        message: rest.trim().to_string(),
+    })
+}
+
+/// Parses the `0:12(5):`, `0(12) :` and `0:12:` location prefixes, returning
+/// the line number and what follows the location
+fn parse_location(s: &str) -> Option<(u32, &str)> {
+    let s = s.trim_start();
+    let (_source, s) = split_number(s)?;
+
+    if let Some(s) = s.strip_prefix('(') {
+        let (line, s) = split_number(s)?;
+        let s = s.strip_prefix(')')?.trim_start().strip_prefix(':')?;
+        return Some((line, s));
+    }
+
+    let (line, s) = split_number(s.strip_prefix(':')?)?;
+    let s = match s.strip_prefix('(') {
+        Some(column) => split_number(column)?.1.strip_prefix(')')?,
+        None => s,
+    };
+    Some((line, s.strip_prefix(':')?))
+}
+
+fn split_number(s: &str) -> Option<(u32, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    Some((s[..end].parse().ok()?, &s[end..]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_mesa_log() {
+        let log = "0:12(5): error: `foo' undeclared\n0:3(1): warning: unused variable\n";
+        let diagnostics = ShaderDiagnostic::parse_info_log(gl::FRAGMENT_SHADER, log);
+        assert_eq!(diagnostics, vec![
+            ShaderDiagnostic::error(gl::FRAGMENT_SHADER, Some(12), "`foo' undeclared".to_string()),
+            ShaderDiagnostic {
+                stage: gl::FRAGMENT_SHADER,
+                severity: Severity::Warning,
+                line: Some(3),
+                message: "unused variable".to_string(),
+            },
+        ]);
+    }
+
+    #[test]
+    fn parses_nvidia_log() {
+        let log = "0(7) : error C1008: undefined variable \"foo\"";
+        let diagnostics = ShaderDiagnostic::parse_info_log(gl::VERTEX_SHADER, log);
+        assert_eq!(diagnostics, vec![
+            ShaderDiagnostic::error(gl::VERTEX_SHADER, Some(7), "undefined variable \"foo\"".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn parses_amd_log() {
+        let log = "ERROR: 0:4: 'foo' : undeclared identifier \n\0";
+        let diagnostics = ShaderDiagnostic::parse_info_log(gl::VERTEX_SHADER, log);
+        assert_eq!(diagnostics, vec![
+            ShaderDiagnostic::error(gl::VERTEX_SHADER, Some(4), "'foo' : undeclared identifier".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn keeps_unrecognised_lines() {
+        let diagnostics = ShaderDiagnostic::parse_info_log(gl::VERTEX_SHADER, "link failed");
+        assert_eq!(diagnostics, vec![
+            ShaderDiagnostic::error(gl::VERTEX_SHADER, None, "link failed".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,144 @@
+//! Transform feedback, capturing the vertices a program outputs into
+//! buffers, e.g. to update particles on the GPU without compute shaders
+
+use super::Program;
+use crate::buffer::{Buffer, BufferType};
+
+/// Transform feedback in progress, started with
+/// `Program::begin_transform_feedback`. Draws made while it's alive write
+/// their captured varyings into the bound feedback buffers. Ends the
+/// feedback when dropped
+pub struct TransformFeedback<'a> {
+    _program: &'a Program,
+    query: gl::types::GLuint,
+    discard: bool,
+}
+
+/// The number of primitives written during a transform feedback, which the
+/// GPU fills in once the draws have finished
+pub struct PrimitivesWritten {
+    query: gl::types::GLuint,
+}
+
+impl Program {
+    /// Uses the program and starts capturing into the buffers bound with
+    /// `Buffer::bind_feedback`. `primitive_mode` is `gl::POINTS`,
+    /// `gl::LINES` or `gl::TRIANGLES`, and has to match the draws made.
+    /// With `discard` set, nothing is rasterized, which is usual when only
+    /// the captured data is wanted
+    pub fn begin_transform_feedback(
+        &self,
+        primitive_mode: gl::types::GLenum,
+        discard: bool,
+    ) -> TransformFeedback<'_> {
+        self.set_used();
+        let mut query: gl::types::GLuint = 0;
+        unsafe {
+            gl::GenQueries(1, &mut query);
+            gl::BeginQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN, query);
+            if discard {
+                gl::Enable(gl::RASTERIZER_DISCARD);
+            }
+            gl::BeginTransformFeedback(primitive_mode);
+        }
+
+        TransformFeedback {
+            _program: self,
+            query,
+            discard,
+        }
+    }
+}
+
+impl TransformFeedback<'_> {
+    /// Stops capturing and returns the query for how many primitives were
+    /// written
+    pub fn end(self) -> PrimitivesWritten {
+        let query = self.query;
+        self.finish();
+        std::mem::forget(self);
+        PrimitivesWritten { query }
+    }
+
+    fn finish(&self) {
+        unsafe {
+            gl::EndTransformFeedback();
+            if self.discard {
+                gl::Disable(gl::RASTERIZER_DISCARD);
+            }
+            gl::EndQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN);
+        }
+    }
+}
+
+impl Drop for TransformFeedback<'_> {
+    fn drop(&mut self) {
+        self.finish();
+        unsafe {
+            gl::DeleteQueries(1, &self.query);
+        }
+    }
+}
+
+impl PrimitivesWritten {
+    /// Whether the count is available without waiting
+    pub fn is_ready(&self) -> bool {
+        let mut available: gl::types::GLuint = 0;
+        unsafe {
+            gl::GetQueryObjectuiv(self.query, gl::QUERY_RESULT_AVAILABLE, &mut available);
+        }
+        available == gl::TRUE as gl::types::GLuint
+    }
+
+    /// The number of primitives written, waiting for the GPU if needed
+    pub fn count(&self) -> u64 {
+        let mut count: gl::types::GLuint64 = 0;
+        unsafe {
+            gl::GetQueryObjectui64v(self.query, gl::QUERY_RESULT, &mut count);
+        }
+        count
+    }
+}
+
+impl Drop for PrimitivesWritten {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteQueries(1, &self.query);
+        }
+    }
+}
+
+impl<B, T> Buffer<B, T>
+where
+    B: BufferType,
+    T: Copy,
+{
+    /// Binds the whole buffer as the transform feedback buffer at `index`.
+    /// Give it storage first, e.g. with `allocate`
+    pub fn bind_feedback(&self, index: gl::types::GLuint) {
+        unsafe {
+            gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, index, self.id());
+        }
+    }
+
+    /// Binds `len` elements starting at element `offset` as the transform
+    /// feedback buffer at `index`
+    pub fn bind_feedback_range(&self, index: gl::types::GLuint, offset: usize, len: usize) {
+        assert!(
+            offset.checked_add(len).is_some_and(|end| end <= self.capacity()),
+            "Feedback range of {} elements at {} is past the end of the buffer ({} elements)",
+            len,
+            offset,
+            self.capacity()
+        );
+        unsafe {
+            gl::BindBufferRange(
+                gl::TRANSFORM_FEEDBACK_BUFFER,
+                index,
+                self.id(),
+                (offset * std::mem::size_of::<T>()) as gl::types::GLintptr,
+                (len * std::mem::size_of::<T>()) as gl::types::GLsizeiptr,
+            );
+        }
+    }
+}
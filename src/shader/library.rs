@@ -0,0 +1,119 @@
+//! Ready made programs for the shaders nearly every application needs.
+//!
+//! They share these attribute locations:
+//!
+//! | location | attribute | type |
+//! |---|---|---|
+//! | [`ATTRIB_POSITION`] | position | `vec3` |
+//! | [`ATTRIB_COLOR`] | per vertex color | `vec4` |
+//! | [`ATTRIB_TEX_COORDS`] | texture coordinates | `vec2` |
+//!
+//! and every program except the blit transforms positions by the `mat4`
+//! uniform `mvp`.
+
+use super::Program;
+
+pub const ATTRIB_POSITION: gl::types::GLuint = 0;
+pub const ATTRIB_COLOR: gl::types::GLuint = 1;
+pub const ATTRIB_TEX_COORDS: gl::types::GLuint = 2;
+
+const BASIC_VERT: &str = include_str!("../res/basic_vertex_shader.glsl");
+const SOLID_COLOR_FRAG: &str = include_str!("../res/solid_color_frag_shader.glsl");
+const VERTEX_COLOR_VERT: &str = include_str!("../res/vertex_color_vertex_shader.glsl");
+const VERTEX_COLOR_FRAG: &str = include_str!("../res/vertex_color_frag_shader.glsl");
+const TEXTURED_VERT: &str = include_str!("../res/textured_vertex_shader.glsl");
+const TEXTURED_TINT_FRAG: &str = include_str!("../res/textured_tint_frag_shader.glsl");
+const TEXTURED_ALPHA_FRAG: &str = include_str!("../res/textured_alpha_frag_shader.glsl");
+pub(crate) const FULLSCREEN_VERT: &str = include_str!("../res/fullscreen_vertex_shader.glsl");
+const BLIT_FRAG: &str = include_str!("../res/blit_frag_shader.glsl");
+
+impl Program {
+    /// Fills geometry with a single color.
+    ///
+    /// Attributes: `position`. Uniforms: `mvp` (`mat4`), `color` (`vec4`)
+    pub fn solid_color() -> Result<Program, String> {
+        Program::from_src(BASIC_VERT, SOLID_COLOR_FRAG)
+    }
+
+    /// Interpolates a color given per vertex.
+    ///
+    /// Attributes: `position`, `vertexColor`. Uniforms: `mvp` (`mat4`)
+    pub fn vertex_color() -> Result<Program, String> {
+        Program::from_src(VERTEX_COLOR_VERT, VERTEX_COLOR_FRAG)
+    }
+
+    /// Samples a texture and multiplies it by a tint.
+    ///
+    /// Attributes: `position`, `texCoords`. Uniforms: `mvp` (`mat4`),
+    /// `tex` (`sampler2D`), `tint` (`vec4`, white leaves the texture as is)
+    pub fn textured_tint() -> Result<Program, String> {
+        Program::from_src(TEXTURED_VERT, TEXTURED_TINT_FRAG)
+    }
+
+    /// Uses the red channel of a texture as the alpha of a single color,
+    /// e.g. for glyphs or masks stored in a `REDTexture`.
+    ///
+    /// Attributes: `position`, `texCoords`. Uniforms: `mvp` (`mat4`),
+    /// `tex` (`sampler2D`), `color` (`vec4`)
+    pub fn textured_alpha() -> Result<Program, String> {
+        Program::from_src(TEXTURED_VERT, TEXTURED_ALPHA_FRAG)
+    }
+
+    /// Copies a texture over the whole viewport. It takes no attributes,
+    /// draw it with `gl::DrawArrays(gl::TRIANGLES, 0, 3)` with any vertex
+    /// array bound.
+    ///
+    /// Uniforms: `tex` (`sampler2D`)
+    pub fn fullscreen_blit() -> Result<Program, String> {
+        Program::from_src(FULLSCREEN_VERT, BLIT_FRAG)
+    }
+}
+
+#[cfg(all(test, feature = "validation"))]
+mod test {
+    use super::*;
+    use crate::ProgramBuilder;
+
+    /// The sources of every built-in program, for validating them offline
+    fn builders() -> Vec<(&'static str, ProgramBuilder)> {
+        [
+            ("solid_color", BASIC_VERT, SOLID_COLOR_FRAG),
+            ("vertex_color", VERTEX_COLOR_VERT, VERTEX_COLOR_FRAG),
+            ("textured_tint", TEXTURED_VERT, TEXTURED_TINT_FRAG),
+            ("textured_alpha", TEXTURED_VERT, TEXTURED_ALPHA_FRAG),
+            ("fullscreen_blit", FULLSCREEN_VERT, BLIT_FRAG),
+        ]
+        .into_iter()
+        .map(|(name, vert, frag)| (name, ProgramBuilder::new().vertex(vert).fragment(frag)))
+        .collect()
+    }
+
+    #[test]
+    fn built_in_programs_are_valid() {
+        for (name, builder) in builders() {
+            if let Err(diagnostics) = builder.validate() {
+                panic!("{} is invalid: {:?}", name, diagnostics);
+            }
+        }
+    }
+
+    #[test]
+    fn built_in_uniforms() {
+        let uniforms = |builder: &ProgramBuilder| {
+            let interface = builder.validate().unwrap();
+            let mut names: Vec<String> = interface.uniforms.into_iter().map(|u| u.name).collect();
+            names.sort();
+            names
+        };
+        let expected = [
+            vec!["color", "mvp"],
+            vec!["mvp"],
+            vec!["mvp", "tex", "tint"],
+            vec!["color", "mvp", "tex"],
+            vec!["tex"],
+        ];
+        for ((name, builder), expected) in builders().iter().zip(expected) {
+            assert_eq!(uniforms(builder), expected, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,477 @@
+mod builder;
+mod cache;
+mod compute;
+mod diagnostic;
+mod feedback;
+mod library;
+mod uniform;
+#[cfg(feature = "validation")]
+mod validate;
+mod variants;
+
+pub use builder::ProgramBuilder;
+pub use cache::ProgramCache;
+pub use compute::{memory_barrier, memory_barrier_by_region, ComputeProgram};
+pub use diagnostic::{Severity, ShaderDiagnostic};
+pub use feedback::{PrimitivesWritten, TransformFeedback};
+pub use library::{ATTRIB_COLOR, ATTRIB_POSITION, ATTRIB_TEX_COORDS};
+pub(crate) use library::FULLSCREEN_VERT;
+pub use uniform::{Uniform, UniformValue, Uniforms};
+#[cfg(feature = "validation")]
+pub use validate::{validate_shader, ShaderInterface, UniformDecl};
+pub use variants::ProgramVariants;
+
+use nalgebra::Matrix4;
+use std::{cell::{Cell, RefCell}, collections::HashMap, ffi::{CStr, CString}};
+
+pub struct Program {
+    id: gl::types::GLuint,
+    uniform_map: RefCell<std::collections::HashMap<String, i32>>,
+    /// Last value set at each location, when the value cache is enabled
+    value_cache: RefCell<Option<HashMap<i32, UniformValue>>>,
+    uniform_stats: Cell<UniformStats>,
+}
+
+/// How many uniform updates were sent to the driver, and how many were
+/// skipped by the value cache because the uniform already had that value
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UniformStats {
+    pub issued: u64,
+    pub skipped: u64,
+}
+
+#[allow(dead_code)]
+impl Program {
+    pub fn from_shaders(shaders: &[Shader]) -> Result<Program, String> {
+        Program::link(shaders, |_| {})
+    }
+
+    /// Attaches and links `shaders`, giving `before_link` a chance to set
+    /// program state that only takes effect at link time
+    pub(crate) fn link<F>(shaders: &[Shader], before_link: F) -> Result<Program, String>
+    where
+        F: FnOnce(gl::types::GLuint),
+    {
+        let program_id = unsafe { gl::CreateProgram() };
+
+        for shader in shaders {
+            unsafe {
+                gl::AttachShader(program_id, shader.id());
+            }
+        }
+
+        before_link(program_id);
+
+        unsafe {
+            gl::LinkProgram(program_id);
+        }
+
+        for shader in shaders {
+            unsafe {
+                gl::DetachShader(program_id, shader.id());
+            }
+        }
+
+        Program::from_linked(program_id)
+    }
+
+    /// Creates a program from a binary previously returned by
+    /// [`Program::binary`]. Drivers are free to reject binaries, e.g. after
+    /// an update, in which case the program has to be built from source
+    pub fn from_binary(format: gl::types::GLenum, binary: &[u8]) -> Result<Program, String> {
+        let program_id = unsafe { gl::CreateProgram() };
+        unsafe {
+            gl::ProgramBinary(
+                program_id,
+                format,
+                binary.as_ptr() as *const gl::types::GLvoid,
+                binary.len() as gl::types::GLsizei,
+            );
+        }
+
+        Program::from_linked(program_id)
+    }
+
+    fn from_linked(program_id: gl::types::GLuint) -> Result<Program, String> {
+        let mut success: gl::types::GLint = 1;
+        unsafe {
+            gl::GetProgramiv(program_id, gl::LINK_STATUS, &mut success);
+        }
+
+        if success == 0 {
+            let mut len: gl::types::GLint = 0;
+            unsafe {
+                gl::GetProgramiv(program_id, gl::INFO_LOG_LENGTH, &mut len);
+            }
+
+            let error = create_whitespace_cstring_with_len(len as usize);
+
+            unsafe {
+                gl::GetProgramInfoLog(
+                    program_id,
+                    len,
+                    std::ptr::null_mut(),
+                    error.as_ptr() as *mut gl::types::GLchar,
+                );
+                gl::DeleteProgram(program_id);
+            }
+
+            return Err(error.to_string_lossy().into_owned());
+        }
+
+        Ok(Program {
+            id: program_id,
+            uniform_map: RefCell::new(HashMap::<String, i32>::new()),
+            value_cache: RefCell::new(None),
+            uniform_stats: Cell::new(UniformStats::default()),
+        })
+    }
+
+    /// The driver specific binary of this program along with its format, if
+    /// the driver supports retrieving it. Only reliable for programs linked
+    /// with `gl::PROGRAM_BINARY_RETRIEVABLE_HINT` set
+    pub fn binary(&self) -> Option<(gl::types::GLenum, Vec<u8>)> {
+        let mut len: gl::types::GLint = 0;
+        unsafe {
+            gl::GetProgramiv(self.id, gl::PROGRAM_BINARY_LENGTH, &mut len);
+        }
+        if len <= 0 {
+            return None;
+        }
+
+        let mut format: gl::types::GLenum = 0;
+        let mut written: gl::types::GLsizei = 0;
+        let mut binary = vec![0_u8; len as usize];
+        unsafe {
+            gl::GetProgramBinary(
+                self.id,
+                len,
+                &mut written,
+                &mut format,
+                binary.as_mut_ptr() as *mut gl::types::GLvoid,
+            );
+        }
+        binary.truncate(written as usize);
+
+        Some((format, binary))
+    }
+    
+    pub fn from_src(vert_src: &str, frag_src: &str) -> Result<Program, String> {
+        ProgramBuilder::new()
+            .vertex(vert_src)
+            .fragment(frag_src)
+            .build()
+    }
+    
+    pub fn set_used(&self) {
+        unsafe {
+            gl::UseProgram(self.id);
+        }
+    }
+
+    pub fn get_uniform_location(&self, name: String) -> i32 {
+        self.uniform_location(&name)
+    }
+
+    /// Same as `get_uniform_location`, without needing an owned name once
+    /// the location is cached
+    pub fn uniform_location(&self, name: &str) -> i32 {
+        let mut map = self.uniform_map.borrow_mut();
+        if let Some(index) = map.get(name) {
+            *index
+        } else {
+            unsafe {
+                let c_str = CString::new(name).unwrap();
+                let index = gl::GetUniformLocation(self.id, c_str.as_ptr());
+                map.insert(name.to_string(), index);
+                index
+            }
+        }
+    }
+
+    /// Sets any [`Uniform`] value on this program, which has to be in use.
+    /// With the value cache enabled, setting a uniform to the value it
+    /// already has doesn't call into the driver
+    pub fn set_uniform<U>(&self, name: &str, value: &U)
+    where
+        U: Uniform + ?Sized,
+    {
+        let location = self.uniform_location(name);
+        let mut stats = self.uniform_stats.get();
+        let upload = record_update(self.value_cache.borrow_mut().as_mut(), &mut stats, location, value);
+        self.uniform_stats.set(stats);
+
+        if upload {
+            value.set_uniform(location);
+        }
+    }
+
+    /// Turns the uniform value cache on or off. It's off by default, since
+    /// it's wrong if the program's uniforms are also set through raw `gl`
+    /// calls. Turning it on starts with an empty cache
+    pub fn set_value_cache(&self, enabled: bool) {
+        *self.value_cache.borrow_mut() = enabled.then(HashMap::new);
+    }
+
+    pub fn uniform_stats(&self) -> UniformStats {
+        self.uniform_stats.get()
+    }
+
+    pub fn reset_uniform_stats(&self) {
+        self.uniform_stats.set(UniformStats::default());
+    }
+    
+    /// Connects the uniform block called `name` to the uniform buffer
+    /// binding point `binding`
+    pub fn bind_uniform_block(&self, name: &str, binding: gl::types::GLuint) -> Result<(), String> {
+        let index = self.uniform_block_index(name)
+            .ok_or_else(|| format!("Program has no uniform block named `{}`", name))?;
+        unsafe {
+            gl::UniformBlockBinding(self.id, index, binding);
+        }
+        Ok(())
+    }
+
+    /// The size in bytes the linker gave the uniform block called `name`
+    pub fn uniform_block_data_size(&self, name: &str) -> Option<usize> {
+        let index = self.uniform_block_index(name)?;
+        let mut size: gl::types::GLint = 0;
+        unsafe {
+            gl::GetActiveUniformBlockiv(self.id, index, gl::UNIFORM_BLOCK_DATA_SIZE, &mut size);
+        }
+        Some(size as usize)
+    }
+
+    fn uniform_block_index(&self, name: &str) -> Option<gl::types::GLuint> {
+        let c_str = CString::new(name).ok()?;
+        let index = unsafe { gl::GetUniformBlockIndex(self.id, c_str.as_ptr()) };
+        if index == gl::INVALID_INDEX {
+            None
+        } else {
+            Some(index)
+        }
+    }
+
+    /// Connects the shader storage block called `name` to the shader storage
+    /// buffer binding point `binding`
+    pub fn bind_storage_block(&self, name: &str, binding: gl::types::GLuint) -> Result<(), String> {
+        let c_str = CString::new(name)
+            .map_err(|_| format!("Storage block name `{}` contains a nul byte", name))?;
+        let index = unsafe {
+            gl::GetProgramResourceIndex(self.id, gl::SHADER_STORAGE_BLOCK, c_str.as_ptr())
+        };
+        if index == gl::INVALID_INDEX {
+            return Err(format!("Program has no shader storage block named `{}`", name));
+        }
+        unsafe {
+            gl::ShaderStorageBlockBinding(self.id, index, binding);
+        }
+        Ok(())
+    }
+
+    pub fn set_uniform_m4f(&self, name: String, matrix: &Matrix4<f32>) {
+        self.set_uniform(&name, matrix);
+    }
+
+    pub fn set_uniform_glm_m4(&self, name: String, matrix: &nalgebra_glm::Mat4) {
+        self.set_uniform(&name, matrix);
+    }
+
+    pub fn set_bool(&self, name: String, value: bool) {
+        self.set_uniform(&name, &value);
+    }
+    
+    pub fn set_int(&self, name: String, value: i32) {
+        self.set_uniform(&name, &value);
+    }
+    
+    pub fn set_float(&self, name: String, value: f32) {
+        self.set_uniform(&name, &value);
+    }
+
+    pub fn set_3float(&self, name: String, value: [f32;3]) {
+        self.set_uniform(&name, &value);
+    }
+
+    pub fn set_double(&self, name: String, value: f64) {
+        self.set_uniform(&name, &value);
+    }
+}
+
+impl Drop for Program {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.id);
+        }
+    }
+}
+
+pub struct Shader {
+    id: gl::types::GLuint,
+}
+
+impl Shader {
+    pub fn from_source(source: &CStr, kind: gl::types::GLenum) -> Result<Shader, String> {
+        let id = shader_from_source(source, kind)?;
+        Ok(Shader { id })
+    }
+
+    /// Like [`Shader::from_source`], but with the driver's info log split
+    /// into [`ShaderDiagnostic`]s, the same form the offline validator
+    /// reports, so the two can be compared
+    pub fn from_source_diagnostics(
+        source: &CStr,
+        kind: gl::types::GLenum,
+    ) -> Result<Shader, Vec<ShaderDiagnostic>> {
+        Shader::from_source(source, kind).map_err(|log| {
+            let diagnostics = ShaderDiagnostic::parse_info_log(kind, &log);
+            if diagnostics.is_empty() {
+                vec![ShaderDiagnostic::error(kind, None, "compilation failed without an info log".to_string())]
+            } else {
+                diagnostics
+            }
+        })
+    }
+
+    pub fn from_vert_source(source: &CStr) -> Result<Shader, String> {
+        Shader::from_source(source, gl::VERTEX_SHADER)
+    }
+
+    pub fn from_frag_source(source: &CStr) -> Result<Shader, String> {
+        Shader::from_source(source, gl::FRAGMENT_SHADER)
+    }
+
+    pub fn from_geom_source(source: &CStr) -> Result<Shader, String> {
+        Shader::from_source(source, gl::GEOMETRY_SHADER)
+    }
+
+    pub fn from_tess_control_source(source: &CStr) -> Result<Shader, String> {
+        Shader::from_source(source, gl::TESS_CONTROL_SHADER)
+    }
+
+    pub fn from_tess_eval_source(source: &CStr) -> Result<Shader, String> {
+        Shader::from_source(source, gl::TESS_EVALUATION_SHADER)
+    }
+
+    pub fn from_compute_source(source: &CStr) -> Result<Shader, String> {
+        Shader::from_source(source, gl::COMPUTE_SHADER)
+    }
+
+    pub fn id(&self) -> gl::types::GLuint {
+        self.id
+    }
+}
+
+impl Drop for Shader {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteShader(self.id);
+        }
+    }
+}
+
+fn shader_from_source(source: &CStr, kind: gl::types::GLenum) -> Result<gl::types::GLuint, String> {
+    let id = unsafe { gl::CreateShader(kind) };
+    unsafe {
+        gl::ShaderSource(id, 1, &source.as_ptr(), std::ptr::null());
+        gl::CompileShader(id);
+    }
+
+    let mut success: gl::types::GLint = 1;
+    unsafe {
+        gl::GetShaderiv(id, gl::COMPILE_STATUS, &mut success);
+    }
+
+    if success == 0 {
+        let mut len: gl::types::GLint = 0;
+        unsafe {
+            gl::GetShaderiv(id, gl::INFO_LOG_LENGTH, &mut len);
+        }
+
+        let error = create_whitespace_cstring_with_len(len as usize);
+
+        unsafe {
+            gl::GetShaderInfoLog(
+                id,
+                len,
+                std::ptr::null_mut(),
+                error.as_ptr() as *mut gl::types::GLchar,
+            );
+        }
+
+        return Err(error.to_string_lossy().into_owned());
+    }
+
+    Ok(id)
+}
+
+/// Counts a uniform update at `location` in `stats`, returning whether it
+/// has to be sent to the driver, which it doesn't if `cache` says the
+/// uniform already has that value
+fn record_update<U>(
+    cache: Option<&mut HashMap<i32, UniformValue>>,
+    stats: &mut UniformStats,
+    location: i32,
+    value: &U,
+) -> bool
+where
+    U: Uniform + ?Sized,
+{
+    if let Some(cache) = cache {
+        let new_value = value.to_value();
+        if cache.get(&location) == Some(&new_value) {
+            stats.skipped += 1;
+            return false;
+        }
+        cache.insert(location, new_value);
+    }
+
+    stats.issued += 1;
+    true
+}
+
+fn create_whitespace_cstring_with_len(len: usize) -> CString {
+    // allocate buffer of correct size
+    let mut buffer: Vec<u8> = Vec::with_capacity(len + 1);
+    // fill it with len spaces
+    buffer.extend([b' '].iter().cycle().take(len));
+    // convert buffer to CString
+    unsafe { CString::from_vec_unchecked(buffer) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn value_cache_skips_identical_values() {
+        let mut cache = HashMap::new();
+        let mut stats = UniformStats::default();
+
+        assert!(record_update(Some(&mut cache), &mut stats, 3, &1.5_f32));
+        assert!(!record_update(Some(&mut cache), &mut stats, 3, &1.5_f32));
+        assert_eq!(stats, UniformStats { issued: 1, skipped: 1 });
+    }
+
+    #[test]
+    fn value_cache_uploads_changed_values() {
+        let mut cache = HashMap::new();
+        let mut stats = UniformStats::default();
+
+        assert!(record_update(Some(&mut cache), &mut stats, 3, &1.5_f32));
+        assert!(record_update(Some(&mut cache), &mut stats, 3, &2.5_f32));
+        // same value at another location
+        assert!(record_update(Some(&mut cache), &mut stats, 4, &2.5_f32));
+        assert_eq!(stats, UniformStats { issued: 3, skipped: 0 });
+        assert_eq!(cache.get(&3), Some(&UniformValue::Float(2.5)));
+    }
+
+    #[test]
+    fn disabled_cache_always_uploads() {
+        let mut stats = UniformStats::default();
+
+        assert!(record_update(None, &mut stats, 3, &1.5_f32));
+        assert!(record_update(None, &mut stats, 3, &1.5_f32));
+        assert_eq!(stats, UniformStats { issued: 2, skipped: 0 });
+    }
+}
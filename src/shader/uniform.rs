@@ -0,0 +1,239 @@
+use nalgebra_glm::{IVec2, IVec3, IVec4, Mat2, Mat3, Mat4, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+
+use super::Program;
+use crate::ColorF32;
+
+/// A value that can be set as a uniform with [`Program::set_uniform`]
+pub trait Uniform {
+    /// Sets the uniform at `location` in the program currently in use
+    fn set_uniform(&self, location: i32);
+
+    /// An owned copy of the value, used by the program's value cache
+    fn to_value(&self) -> UniformValue;
+}
+
+/// A group of uniforms that are set together, usually implemented with
+/// `#[derive(Uniforms)]` from the `derive` feature
+pub trait Uniforms {
+    /// Makes `program` the current program and sets every uniform on it,
+    /// attaching sampler fields to their texture units
+    fn apply(&self, program: &Program);
+
+    /// The names of the uniforms set by `apply`
+    fn uniform_names() -> &'static [&'static str];
+}
+
+macro_rules! impl_uniform {
+    ($($ty:ty => $variant:ident |$v:ident, $loc:ident| $set:expr),* $(,)?) => {
+        $(
+            impl Uniform for $ty {
+                fn set_uniform(&self, location: i32) {
+                    let ($v, $loc) = (self, location);
+                    unsafe { $set }
+                }
+
+                fn to_value(&self) -> UniformValue {
+                    UniformValue::$variant((*self).into())
+                }
+            }
+        )*
+    };
+}
+
+impl_uniform! {
+    bool => Bool |v, loc| gl::Uniform1i(loc, *v as i32),
+    i32 => Int |v, loc| gl::Uniform1i(loc, *v),
+    u32 => UInt |v, loc| gl::Uniform1ui(loc, *v),
+    f32 => Float |v, loc| gl::Uniform1f(loc, *v),
+    f64 => Double |v, loc| gl::Uniform1d(loc, *v),
+    [f32; 2] => Vec2 |v, loc| gl::Uniform2f(loc, v[0], v[1]),
+    [f32; 3] => Vec3 |v, loc| gl::Uniform3f(loc, v[0], v[1], v[2]),
+    [f32; 4] => Vec4 |v, loc| gl::Uniform4f(loc, v[0], v[1], v[2], v[3]),
+    [i32; 2] => IVec2 |v, loc| gl::Uniform2i(loc, v[0], v[1]),
+    [i32; 3] => IVec3 |v, loc| gl::Uniform3i(loc, v[0], v[1], v[2]),
+    [i32; 4] => IVec4 |v, loc| gl::Uniform4i(loc, v[0], v[1], v[2], v[3]),
+    Vec2 => Vec2 |v, loc| gl::Uniform2f(loc, v.x, v.y),
+    Vec3 => Vec3 |v, loc| gl::Uniform3f(loc, v.x, v.y, v.z),
+    Vec4 => Vec4 |v, loc| gl::Uniform4f(loc, v.x, v.y, v.z, v.w),
+    IVec2 => IVec2 |v, loc| gl::Uniform2i(loc, v.x, v.y),
+    IVec3 => IVec3 |v, loc| gl::Uniform3i(loc, v.x, v.y, v.z),
+    IVec4 => IVec4 |v, loc| gl::Uniform4i(loc, v.x, v.y, v.z, v.w),
+    UVec2 => UVec2 |v, loc| gl::Uniform2ui(loc, v.x, v.y),
+    UVec3 => UVec3 |v, loc| gl::Uniform3ui(loc, v.x, v.y, v.z),
+    UVec4 => UVec4 |v, loc| gl::Uniform4ui(loc, v.x, v.y, v.z, v.w),
+    Mat2 => Mat2 |v, loc| gl::UniformMatrix2fv(loc, 1, gl::FALSE, v.as_ptr()),
+    Mat3 => Mat3 |v, loc| gl::UniformMatrix3fv(loc, 1, gl::FALSE, v.as_ptr()),
+    Mat4 => Mat4 |v, loc| gl::UniformMatrix4fv(loc, 1, gl::FALSE, v.as_ptr()),
+    ColorF32 => Vec3 |v, loc| gl::Uniform3f(loc, v.r, v.g, v.b),
+}
+
+/// Arrays of scalars are set as GLSL arrays, e.g. `uniform float weights[8]`
+impl Uniform for [f32] {
+    fn set_uniform(&self, location: i32) {
+        unsafe {
+            gl::Uniform1fv(location, self.len() as i32, self.as_ptr());
+        }
+    }
+
+    fn to_value(&self) -> UniformValue {
+        UniformValue::FloatArray(self.to_vec())
+    }
+}
+
+impl Uniform for [i32] {
+    fn set_uniform(&self, location: i32) {
+        unsafe {
+            gl::Uniform1iv(location, self.len() as i32, self.as_ptr());
+        }
+    }
+
+    fn to_value(&self) -> UniformValue {
+        UniformValue::IntArray(self.to_vec())
+    }
+}
+
+impl<U> Uniform for &U
+where
+    U: Uniform + ?Sized,
+{
+    fn set_uniform(&self, location: i32) {
+        (**self).set_uniform(location);
+    }
+
+    fn to_value(&self) -> UniformValue {
+        (**self).to_value()
+    }
+}
+
+/// An owned uniform value, for storing uniforms to be set later, e.g. in a
+/// [`Material`](crate::Material)
+#[derive(Clone, Debug, PartialEq)]
+pub enum UniformValue {
+    Bool(bool),
+    Int(i32),
+    UInt(u32),
+    Float(f32),
+    Double(f64),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    IVec2(IVec2),
+    IVec3(IVec3),
+    IVec4(IVec4),
+    UVec2(UVec2),
+    UVec3(UVec3),
+    UVec4(UVec4),
+    Mat2(Mat2),
+    Mat3(Mat3),
+    Mat4(Mat4),
+    FloatArray(Vec<f32>),
+    IntArray(Vec<i32>),
+}
+
+impl Uniform for UniformValue {
+    fn set_uniform(&self, location: i32) {
+        match self {
+            UniformValue::Bool(v) => v.set_uniform(location),
+            UniformValue::Int(v) => v.set_uniform(location),
+            UniformValue::UInt(v) => v.set_uniform(location),
+            UniformValue::Float(v) => v.set_uniform(location),
+            UniformValue::Double(v) => v.set_uniform(location),
+            UniformValue::Vec2(v) => v.set_uniform(location),
+            UniformValue::Vec3(v) => v.set_uniform(location),
+            UniformValue::Vec4(v) => v.set_uniform(location),
+            UniformValue::IVec2(v) => v.set_uniform(location),
+            UniformValue::IVec3(v) => v.set_uniform(location),
+            UniformValue::IVec4(v) => v.set_uniform(location),
+            UniformValue::UVec2(v) => v.set_uniform(location),
+            UniformValue::UVec3(v) => v.set_uniform(location),
+            UniformValue::UVec4(v) => v.set_uniform(location),
+            UniformValue::Mat2(v) => v.set_uniform(location),
+            UniformValue::Mat3(v) => v.set_uniform(location),
+            UniformValue::Mat4(v) => v.set_uniform(location),
+            UniformValue::FloatArray(v) => v.as_slice().set_uniform(location),
+            UniformValue::IntArray(v) => v.as_slice().set_uniform(location),
+        }
+    }
+
+    fn to_value(&self) -> UniformValue {
+        self.clone()
+    }
+}
+
+macro_rules! impl_from_uniform_value {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for UniformValue {
+                fn from(value: $ty) -> Self {
+                    UniformValue::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_uniform_value! {
+    bool => Bool,
+    i32 => Int,
+    u32 => UInt,
+    f32 => Float,
+    f64 => Double,
+    Vec2 => Vec2,
+    Vec3 => Vec3,
+    Vec4 => Vec4,
+    [f32; 2] => Vec2,
+    [f32; 3] => Vec3,
+    [f32; 4] => Vec4,
+    IVec2 => IVec2,
+    IVec3 => IVec3,
+    IVec4 => IVec4,
+    [i32; 2] => IVec2,
+    [i32; 3] => IVec3,
+    [i32; 4] => IVec4,
+    UVec2 => UVec2,
+    UVec3 => UVec3,
+    UVec4 => UVec4,
+    Mat2 => Mat2,
+    Mat3 => Mat3,
+    Mat4 => Mat4,
+    Vec<f32> => FloatArray,
+    Vec<i32> => IntArray,
+}
+
+impl From<ColorF32> for UniformValue {
+    fn from(value: ColorF32) -> Self {
+        UniformValue::Vec3(value.into())
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod test {
+    use crate::{RGBTexture, Uniforms};
+    use nalgebra_glm::{Mat4, Vec3};
+
+    #[derive(Uniforms)]
+    #[allow(dead_code)]
+    struct Material<'a> {
+        #[uniform(name = "uColor")]
+        color: Vec3,
+        shininess: f32,
+        #[uniform(sampler = 1)]
+        diffuse: &'a RGBTexture,
+        #[uniform(skip)]
+        label: String,
+    }
+
+    #[derive(Uniforms)]
+    #[uniform(prefix = "light.")]
+    #[allow(dead_code)]
+    struct Light {
+        position: Vec3,
+        transform: Mat4,
+    }
+
+    #[test]
+    fn derived_names() {
+        assert_eq!(Material::uniform_names(), &["uColor", "shininess", "diffuse"]);
+        assert_eq!(Light::uniform_names(), &["light.position", "light.transform"]);
+    }
+}
@@ -0,0 +1,555 @@
+//! Offline GLSL validation built on naga's GLSL frontend, so shaders can be
+//! checked in unit tests or a build script without a GL context.
+//!
+//! naga expects Vulkan flavoured GLSL, so OpenGL sources are adapted first:
+//! the version is raised to 450, loose uniforms are moved into blocks of
+//! their own, blocks get bindings and each `samplerXX` is split into a
+//! texture and a sampler (`gl_VertexID` and `gl_InstanceID` are renamed
+//! to their Vulkan names too). This is done in place, so diagnostics keep the
+//! line numbers of the original source. Sampler arrays and samplers passed
+//! to user functions aren't adapted and will be reported as errors.
+//!
+//! Only vertex, fragment and compute shaders can be validated.
+
+use std::error::Error;
+
+use naga::{
+    front::glsl::{Frontend, Options},
+    valid::{Capabilities, ValidationFlags, Validator},
+    ShaderStage, Span,
+};
+
+use super::{builder::stage_name, ProgramBuilder, ShaderDiagnostic};
+
+/// A uniform declared outside of any block
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UniformDecl {
+    pub name: String,
+    /// The GLSL type as written, e.g. `mat4` or `sampler2D`
+    pub ty: String,
+    /// The array size expression, for array uniforms
+    pub array_len: Option<String>,
+}
+
+/// The uniforms and blocks declared by one or more shader stages. These are
+/// the declared names, a driver may still optimise some of them out
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShaderInterface {
+    pub uniforms: Vec<UniformDecl>,
+    pub uniform_blocks: Vec<String>,
+    pub storage_blocks: Vec<String>,
+}
+
+impl ShaderInterface {
+    pub fn uniform(&self, name: &str) -> Option<&UniformDecl> {
+        self.uniforms.iter().find(|u| u.name == name)
+    }
+
+    /// The declared uniforms that aren't in `set`, i.e. ones the Rust code
+    /// never sets
+    pub fn missing_uniforms<'a>(&'a self, set: &[&str]) -> Vec<&'a str> {
+        self.uniforms
+            .iter()
+            .map(|u| u.name.as_str())
+            .filter(|name| !set.contains(name))
+            .collect()
+    }
+
+    /// The names in `set` that the shaders don't declare, e.g. typos or
+    /// uniforms that were since removed from the shader
+    pub fn unknown_uniforms<'s>(&self, set: &[&'s str]) -> Vec<&'s str> {
+        set.iter()
+            .copied()
+            .filter(|name| self.uniform(name).is_none())
+            .collect()
+    }
+
+    fn merge(&mut self, other: ShaderInterface) {
+        for uniform in other.uniforms {
+            if self.uniform(&uniform.name).is_none() {
+                self.uniforms.push(uniform);
+            }
+        }
+        for block in other.uniform_blocks {
+            if !self.uniform_blocks.contains(&block) {
+                self.uniform_blocks.push(block);
+            }
+        }
+        for block in other.storage_blocks {
+            if !self.storage_blocks.contains(&block) {
+                self.storage_blocks.push(block);
+            }
+        }
+    }
+}
+
+/// Parses and type checks `src` as a shader of the given stage, returning
+/// the uniforms it declares
+pub fn validate_shader(stage: gl::types::GLenum, src: &str) -> Result<ShaderInterface, Vec<ShaderDiagnostic>> {
+    let naga_stage = naga_stage(stage).ok_or_else(|| {
+        vec![ShaderDiagnostic::error(
+            stage,
+            None,
+            format!("{} shaders can't be validated offline", stage_name(stage)),
+        )]
+    })?;
+
+    let adapted = adapt(src);
+    let diagnostic = |span: Span, message: String| {
+        let line = span.is_defined().then(|| {
+            span.location(&adapted.source)
+                .line_number
+                .saturating_sub(adapted.line_offset)
+        });
+        ShaderDiagnostic::error(stage, line, message)
+    };
+
+    let module = Frontend::default()
+        .parse(&Options::from(naga_stage), &adapted.source)
+        .map_err(|errors| {
+            errors.errors
+                .iter()
+                .map(|err| diagnostic(err.meta, err.kind.to_string()))
+                .collect::<Vec<_>>()
+        })?;
+
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|err| {
+            let span = err.spans().last().map_or(Span::UNDEFINED, |(span, _)| *span);
+            let mut message = err.as_inner().to_string();
+            let mut source = err.as_inner().source();
+            while let Some(inner) = source {
+                message.push_str(": ");
+                message.push_str(&inner.to_string());
+                source = inner.source();
+            }
+            vec![diagnostic(span, message)]
+        })?;
+
+    Ok(adapted.interface)
+}
+
+impl ProgramBuilder {
+    /// Validates every stage that can be validated offline, returning the
+    /// combined interface of all of them or the diagnostics of every stage
+    /// that failed
+    pub fn validate(&self) -> Result<ShaderInterface, Vec<ShaderDiagnostic>> {
+        let mut interface = ShaderInterface::default();
+        let mut diagnostics = Vec::new();
+
+        for (kind, src) in self.stages() {
+            if naga_stage(*kind).is_none() {
+                continue;
+            }
+            match validate_shader(*kind, src) {
+                Ok(stage_interface) => interface.merge(stage_interface),
+                Err(mut errors) => diagnostics.append(&mut errors),
+            }
+        }
+
+        if diagnostics.is_empty() {
+            Ok(interface)
+        } else {
+            Err(diagnostics)
+        }
+    }
+}
+
+fn naga_stage(stage: gl::types::GLenum) -> Option<ShaderStage> {
+    match stage {
+        gl::VERTEX_SHADER => Some(ShaderStage::Vertex),
+        gl::FRAGMENT_SHADER => Some(ShaderStage::Fragment),
+        gl::COMPUTE_SHADER => Some(ShaderStage::Compute),
+        _ => None,
+    }
+}
+
+const SAMPLER_SUFFIX: &str = "_rgl_sampler";
+
+struct Adapted {
+    source: String,
+    /// Lines added in front of the original source
+    line_offset: u32,
+    interface: ShaderInterface,
+}
+
+/// Pieces of the adapted source. Only `Original` text gets sampler uses
+/// rewritten
+enum Segment {
+    Original(String),
+    Generated(String),
+}
+
+struct Adapter {
+    segments: Vec<Segment>,
+    interface: ShaderInterface,
+    /// Combined sampler uniforms that were split, with their original type
+    samplers: Vec<(String, String)>,
+    next_binding: u32,
+}
+
+fn adapt(src: &str) -> Adapted {
+    let code = strip_comments(src);
+    let mut adapter = Adapter {
+        segments: Vec::new(),
+        interface: ShaderInterface::default(),
+        samplers: Vec::new(),
+        next_binding: 0,
+    };
+
+    let mut line_offset = 0;
+    if !code.lines().any(|line| line.trim_start().starts_with("#version")) {
+        adapter.segments.push(Segment::Generated("#version 450 core\n".to_string()));
+        line_offset = 1;
+    }
+
+    let bytes = code.as_bytes();
+    let mut depth = 0_u32;
+    let mut start = 0;
+    let mut line_start = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if line_start && c == b'#' {
+            // preprocessor directives run to the end of the line
+            adapter.segments.push(Segment::Original(code[start..i].to_string()));
+            let end = code[i..].find('\n').map_or(code.len(), |n| i + n);
+            let directive = &code[i..end];
+            if directive.trim_start_matches('#').trim_start().starts_with("version") {
+                adapter.segments.push(Segment::Generated("#version 450 core".to_string()));
+            } else {
+                adapter.segments.push(Segment::Original(directive.to_string()));
+            }
+            start = end;
+            i = end;
+            continue;
+        }
+
+        match c {
+            b'\n' => line_start = true,
+            c if c.is_ascii_whitespace() => {}
+            _ => line_start = false,
+        }
+
+        match c {
+            b';' if depth == 0 => {
+                adapter.statement(&code[start..=i], false);
+                start = i + 1;
+            }
+            b'{' => {
+                if depth == 0 {
+                    adapter.statement(&code[start..=i], true);
+                    start = i + 1;
+                }
+                depth += 1;
+            }
+            b'}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    adapter.segments.push(Segment::Original(code[start..=i].to_string()));
+                    start = i + 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    adapter.segments.push(Segment::Original(code[start..].to_string()));
+
+    let mut source = String::with_capacity(code.len() * 2);
+    for segment in &adapter.segments {
+        match segment {
+            Segment::Generated(text) => source.push_str(text),
+            Segment::Original(text) => {
+                // Vulkan GLSL renames these, naga only knows the new names
+                let mut text = replace_word(text, "gl_VertexID", "gl_VertexIndex");
+                text = replace_word(&text, "gl_InstanceID", "gl_InstanceIndex");
+                for (name, ty) in &adapter.samplers {
+                    let combined = format!("{}({}, {}{})", ty, name, name, SAMPLER_SUFFIX);
+                    text = replace_word(&text, name, &combined);
+                }
+                source.push_str(&text);
+            }
+        }
+    }
+
+    Adapted {
+        source,
+        line_offset,
+        interface: adapter.interface,
+    }
+}
+
+impl Adapter {
+    /// Handles a top level statement ending in `;`, or the header of a
+    /// top level block ending in `{`
+    fn statement(&mut self, text: &str, opens_block: bool) {
+        let body_start = text.len() - text.trim_start().len();
+        let (leading, body) = text.split_at(body_start);
+        let newlines = body.matches('\n').count();
+
+        let replacement = if opens_block {
+            self.block_header(body)
+        } else {
+            self.loose_uniform(body)
+        };
+
+        match replacement {
+            Some(replacement) => {
+                self.segments.push(Segment::Original(leading.to_string()));
+                self.segments.push(Segment::Generated(replacement + &"\n".repeat(newlines)));
+            }
+            None => self.segments.push(Segment::Original(text.to_string())),
+        }
+    }
+
+    /// Gives uniform and buffer blocks a binding, which naga requires
+    fn block_header(&mut self, body: &str) -> Option<String> {
+        let tokens = tokenize(body);
+        let keyword = tokens.iter().position(|t| *t == "uniform" || *t == "buffer")?;
+        let name = tokens.get(keyword + 1)?;
+        if tokens[keyword] == "uniform" {
+            self.interface.uniform_blocks.push(name.to_string());
+        } else {
+            self.interface.storage_blocks.push(name.to_string());
+        }
+
+        if tokens.contains(&"binding") {
+            return None;
+        }
+        let binding = self.binding();
+        if tokens[0] == "layout" {
+            let paren = body.find('(')? + 1;
+            Some(format!("{}binding = {}, {}", &body[..paren], binding, &body[paren..]))
+        } else {
+            Some(format!("layout(binding = {}) {}", binding, body))
+        }
+    }
+
+    fn loose_uniform(&mut self, body: &str) -> Option<String> {
+        let tokens = tokenize(body.trim_end_matches(';'));
+        let mut rest = tokens.as_slice();
+
+        if rest.first() == Some(&"layout") {
+            let close = rest.iter().position(|t| *t == ")")?;
+            rest = &rest[close + 1..];
+        }
+        if rest.first() != Some(&"uniform") {
+            return None;
+        }
+        rest = &rest[1..];
+        while matches!(rest.first(), Some(&"lowp") | Some(&"mediump") | Some(&"highp")) {
+            rest = &rest[1..];
+        }
+        let (ty, rest) = rest.split_first()?;
+        if rest.contains(&"=") || rest.contains(&"{") {
+            return None;
+        }
+
+        let declarators: Vec<&[&str]> = rest.split(|t| *t == ",").collect();
+        for declarator in &declarators {
+            let (name, array) = declarator.split_first()?;
+            let array_len = (array.len() > 2).then(|| array[1..array.len() - 1].join(" "));
+            self.interface.uniforms.push(UniformDecl {
+                name: name.to_string(),
+                ty: ty.to_string(),
+                array_len,
+            });
+        }
+
+        let is_sampler = ["sampler", "isampler", "usampler"]
+            .iter()
+            .any(|prefix| ty.starts_with(prefix));
+        if is_sampler {
+            if declarators.iter().any(|d| d.len() != 1) {
+                return None;
+            }
+            let texture_ty = ty.replacen("sampler", "texture", 1).replace("Shadow", "");
+            let sampler_ty = if ty.ends_with("Shadow") { "samplerShadow" } else { "sampler" };
+
+            let mut replacement = String::new();
+            for declarator in declarators {
+                let name = declarator[0];
+                let texture_binding = self.binding();
+                let sampler_binding = self.binding();
+                replacement.push_str(&format!(
+                    "layout(binding = {}) uniform {} {}; layout(binding = {}) uniform {} {}{}; ",
+                    texture_binding, texture_ty, name, sampler_binding, sampler_ty, name, SAMPLER_SUFFIX
+                ));
+                self.samplers.push((name.to_string(), ty.to_string()));
+            }
+            return Some(replacement.trim_end().to_string());
+        }
+
+        if ty.starts_with("image") || *ty == "atomic_uint" {
+            return None;
+        }
+
+        let binding = self.binding();
+        Some(format!(
+            "layout(std140, binding = {}) uniform _rgl_default_{} {{ {} {}; }};",
+            binding,
+            binding,
+            ty,
+            rest.join(" ")
+        ))
+    }
+
+    fn binding(&mut self) -> u32 {
+        self.next_binding += 1;
+        self.next_binding - 1
+    }
+}
+
+/// Blanks out comments, keeping newlines so line numbers don't change
+fn strip_comments(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut chars = src.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+                out.push(' ');
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                    }
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+                out.push(' ');
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Identifiers and numbers become one token each, any other non whitespace
+/// character is a token of its own
+fn tokenize(s: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        let is_word = c.is_ascii_alphanumeric() || c == '_';
+        if let (Some(begin), false) = (start, is_word) {
+            tokens.push(&s[begin..i]);
+            start = None;
+        }
+        if is_word {
+            start.get_or_insert(i);
+        } else if !c.is_whitespace() {
+            tokens.push(&s[i..i + c.len_utf8()]);
+        }
+    }
+    if let Some(begin) = start {
+        tokens.push(&s[begin..]);
+    }
+    tokens
+}
+
+/// Replaces whole word uses of `word`, skipping member accesses like `a.word`
+fn replace_word(text: &str, word: &str, replacement: &str) -> String {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for (i, _) in text.match_indices(word) {
+        let before = text[..i].chars().next_back();
+        let after = text[i + word.len()..].chars().next();
+        let member = text[..i].trim_end().ends_with('.');
+        if before.is_some_and(is_word) || after.is_some_and(is_word) || member {
+            continue;
+        }
+        out.push_str(&text[last..i]);
+        out.push_str(replacement);
+        last = i + word.len();
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEXT_VERT: &str = include_str!("../res/text_vertex_shader.glsl");
+    const TEXT_FRAG: &str = include_str!("../res/text_frag_shader.glsl");
+
+    #[test]
+    fn text_program_is_valid() {
+        let interface = ProgramBuilder::new()
+            .vertex(TEXT_VERT)
+            .fragment(TEXT_FRAG)
+            .validate()
+            .unwrap();
+
+        assert_eq!(interface.uniform("projection").unwrap().ty, "mat4");
+        assert_eq!(interface.uniform("text").unwrap().ty, "sampler2D");
+        assert_eq!(interface.missing_uniforms(&["projection", "textColor"]), vec!["text"]);
+        assert_eq!(interface.unknown_uniforms(&["projection", "textColour"]), vec!["textColour"]);
+    }
+
+    #[test]
+    fn reports_type_errors_on_the_original_line() {
+        let src = "#version 330 core\n\
+                   uniform vec3 tint;\n\
+                   out vec4 color;\n\
+                   void main()\n\
+                   {\n\
+                   \x20   color = tint;\n\
+                   }\n";
+        let diagnostics = validate_shader(gl::FRAGMENT_SHADER, src).unwrap_err();
+        assert_eq!(diagnostics[0].line, Some(6));
+    }
+
+    #[test]
+    fn reports_undeclared_identifiers() {
+        let src = "#version 330 core\nout vec4 color;\nvoid main() {\n    color = missing;\n}\n";
+        let diagnostics = validate_shader(gl::FRAGMENT_SHADER, src).unwrap_err();
+        assert_eq!(diagnostics[0].line, Some(4));
+    }
+
+    #[test]
+    fn collects_blocks_and_arrays() {
+        let src = "#version 430 core\n\
+                   layout(std140) uniform Globals { mat4 view; };\n\
+                   layout(std430) buffer Particles { vec4 positions[]; };\n\
+                   uniform vec4 colors[4], tint; // trailing comment\n\
+                   layout(local_size_x = 64) in;\n\
+                   void main() { positions[0] = view * colors[1] * tint; }\n";
+        let interface = validate_shader(gl::COMPUTE_SHADER, src).unwrap();
+
+        assert_eq!(interface.uniform_blocks, vec!["Globals"]);
+        assert_eq!(interface.storage_blocks, vec!["Particles"]);
+        assert_eq!(interface.uniform("colors").unwrap().array_len.as_deref(), Some("4"));
+        assert_eq!(interface.uniform("tint").unwrap().array_len, None);
+    }
+
+    #[test]
+    fn tokenize_splits_punctuation() {
+        assert_eq!(
+            tokenize("uniform vec4 colors[4],tint"),
+            vec!["uniform", "vec4", "colors", "[", "4", "]", ",", "tint"]
+        );
+    }
+
+    #[test]
+    fn replace_word_skips_members_and_longer_names() {
+        assert_eq!(
+            replace_word("text(tex, a.tex, texel)", "tex", "T"),
+            "text(T, a.tex, texel)"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::{Program, ProgramBuilder, ProgramCache};
+
+/// One shader template compiled into a separate program per combination of
+/// feature defines, e.g. textured/untextured or with/without fog.
+///
+/// Each feature becomes a `#define` right after the `#version` line, so the
+/// template can `#ifdef` on it. A feature written as `NAME=VALUE` defines
+/// `NAME` as `VALUE`. Programs are compiled the first time a combination is
+/// asked for and kept for as long as the `ProgramVariants` lives.
+pub struct ProgramVariants {
+    template: ProgramBuilder,
+    cache: Option<Rc<ProgramCache>>,
+    programs: RefCell<HashMap<Vec<String>, Rc<Program>>>,
+}
+
+impl ProgramVariants {
+    pub fn new(template: ProgramBuilder) -> Self {
+        ProgramVariants {
+            template,
+            cache: None,
+            programs: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Builds the variants through `cache` instead of from source every run
+    pub fn with_cache(mut self, cache: Rc<ProgramCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// The program for the given combination of features, in any order
+    pub fn get(&self, features: &[&str]) -> Result<Rc<Program>, String> {
+        let key = variant_key(features);
+        if let Some(program) = self.programs.borrow().get(&key) {
+            return Ok(program.clone());
+        }
+
+        let builder = self.builder_for(&key);
+        let program = Rc::new(match &self.cache {
+            Some(cache) => cache.build(&builder),
+            None => builder.build(),
+        }?);
+
+        self.programs.borrow_mut().insert(key, program.clone());
+        Ok(program)
+    }
+
+    /// How many variants have been compiled so far
+    pub fn compiled_count(&self) -> usize {
+        self.programs.borrow().len()
+    }
+
+    /// Starts from a clone of the template so settings other than the
+    /// sources, like transform feedback varyings, carry over
+    fn builder_for(&self, features: &[String]) -> ProgramBuilder {
+        self.template
+            .stages()
+            .iter()
+            .fold(self.template.clone(), |builder, (kind, src)| {
+                builder.stage(*kind, &inject_defines(src, features))
+            })
+    }
+}
+
+/// Trims, sorts and dedups the features so any spelling or ordering finds
+/// the same program
+fn variant_key(features: &[&str]) -> Vec<String> {
+    let mut key: Vec<String> = features.iter().map(|f| f.trim().to_string()).collect();
+    key.sort();
+    key.dedup();
+    key
+}
+
+/// Puts a `#define` for every feature after the `#version` line, followed
+/// by a `#line` so compiler errors still point at the template's lines
+fn inject_defines(src: &str, features: &[String]) -> String {
+    if features.is_empty() {
+        return src.to_string();
+    }
+
+    let defines: String = features
+        .iter()
+        .map(|feature| match feature.split_once('=') {
+            Some((name, value)) => format!("#define {} {}\n", name.trim(), value.trim()),
+            None => format!("#define {}\n", feature.trim()),
+        })
+        .collect();
+
+    let version_line = src
+        .lines()
+        .position(|line| line.trim_start().starts_with("#version"));
+
+    match version_line {
+        Some(index) => {
+            let split = src
+                .match_indices('\n')
+                .nth(index)
+                .map_or(src.len(), |(i, _)| i + 1);
+            let (head, tail) = src.split_at(split);
+            let newline = if head.ends_with('\n') { "" } else { "\n" };
+            let next_line = next_line_number(head, index);
+            format!("{}{}{}#line {}\n{}", head, newline, defines, next_line, tail)
+        }
+        // no `#version` means GLSL 1.10, where `#line 0` makes the next
+        // line 1
+        None => format!("{}#line 0\n{}", defines, src),
+    }
+}
+
+/// The `#line` number for the line after the `#version` at `index`. Before
+/// GLSL 3.30 `#line N` numbered the line after it `N + 1` rather than `N`
+fn next_line_number(head: &str, index: usize) -> usize {
+    let version = head
+        .lines()
+        .nth(index)
+        .and_then(|line| line.trim_start().strip_prefix("#version"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|number| number.parse::<u32>().ok());
+
+    match version {
+        Some(version) if version < 330 => index + 1,
+        _ => index + 2,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn key_ignores_order_and_duplicates() {
+        assert_eq!(variant_key(&["FOG", "TEXTURED", "FOG"]), variant_key(&["TEXTURED", "FOG"]));
+    }
+
+    #[test]
+    fn defines_follow_version() {
+        let src = "#version 330 core\nvoid main() {}\n";
+        let features = vec!["FOG".to_string(), "LIGHTS=4".to_string()];
+        assert_eq!(
+            inject_defines(src, &features),
+            "#version 330 core\n#define FOG\n#define LIGHTS 4\n#line 2\nvoid main() {}\n"
+        );
+    }
+
+    #[test]
+    fn key_ignores_surrounding_whitespace() {
+        assert_eq!(variant_key(&[" FOG", "TEXTURED "]), variant_key(&["FOG", "TEXTURED"]));
+    }
+
+    #[test]
+    fn line_before_glsl_330_counts_from_the_next_line() {
+        let src = "// comment\n#version 150\nvoid main() {}\n";
+        let features = vec!["FOG".to_string()];
+        assert_eq!(
+            inject_defines(src, &features),
+            "// comment\n#version 150\n#define FOG\n#line 2\nvoid main() {}\n"
+        );
+    }
+
+    #[test]
+    fn defines_without_version() {
+        let features = vec!["FOG".to_string()];
+        assert_eq!(
+            inject_defines("void main() {}", &features),
+            "#define FOG\n#line 0\nvoid main() {}"
+        );
+    }
+
+    #[test]
+    fn no_features_leaves_source_alone() {
+        assert_eq!(inject_defines("#version 330 core", &[]), "#version 330 core");
+    }
+}
@@ -0,0 +1,74 @@
+//! Fence syncs, for finding out when the GPU has finished the commands
+//! issued before the fence without stalling on them
+
+use std::time::Duration;
+
+/// A fence in the GL command stream, signaled once every command issued
+/// before it has finished
+pub struct Fence {
+    sync: gl::types::GLsync,
+}
+
+impl Fence {
+    /// Inserts a fence after the commands issued so far
+    pub fn new() -> Fence {
+        let sync = unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+        Fence { sync }
+    }
+
+    /// Whether the commands before the fence have finished, without waiting
+    pub fn is_signaled(&self) -> bool {
+        self.wait(Duration::ZERO)
+    }
+
+    /// Waits up to `timeout` for the commands before the fence to finish,
+    /// returning whether they did. The command stream is flushed, so the
+    /// fence is guaranteed to be signaled eventually
+    pub fn wait(&self, timeout: Duration) -> bool {
+        let status = self.client_wait(timeout);
+        status == gl::ALREADY_SIGNALED || status == gl::CONDITION_SATISFIED
+    }
+
+    /// Waits for as long as it takes the commands before the fence to
+    /// finish. Drivers may give up on a single wait early, so this keeps
+    /// waiting until the fence is signaled, and only fails if the wait
+    /// itself does
+    pub fn wait_signaled(&self) -> Result<(), String> {
+        loop {
+            match self.client_wait(Duration::MAX) {
+                gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => return Ok(()),
+                gl::TIMEOUT_EXPIRED => continue,
+                _ => {
+                    return Err(format!(
+                        "glClientWaitSync failed with error 0x{:x}",
+                        unsafe { gl::GetError() }
+                    ))
+                }
+            }
+        }
+    }
+
+    fn client_wait(&self, timeout: Duration) -> gl::types::GLenum {
+        unsafe {
+            gl::ClientWaitSync(
+                self.sync,
+                gl::SYNC_FLUSH_COMMANDS_BIT,
+                timeout.as_nanos().min(u64::MAX as u128) as gl::types::GLuint64,
+            )
+        }
+    }
+}
+
+impl Default for Fence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Fence {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteSync(self.sync);
+        }
+    }
+}
@@ -1,19 +1,39 @@
 extern crate rusttype;
 use nalgebra::Vector2;
-use nalgebra_glm::{pi, Mat4, Vec2};
+use nalgebra_glm::{Mat4, Vec2};
 use std::collections::HashMap;
 type Vector2i = Vector2<i32>;
-use crate::{gl_panic, rectangle::Rect, ArrayBuffer, Program, REDTexture, VertexArray};
+use crate::buffer::{Vertex, VertexAttribute};
+use crate::{gl_panic, rectangle::Rect, ArrayBuffer, Program, ProgramBuilder, ProgramCache, REDTexture, VertexArray};
+use crate::{Filter, SamplerDesc, Wrap};
 use freetype as ft;
 
 pub struct FontContext {
     map: HashMap<char, Character>,
     pub pixel_height: u32,
     text_vao: VertexArray,
-    text_vbo: ArrayBuffer,
+    text_vbo: ArrayBuffer<TextVertex>,
     text_shader: Program,
 }
 
+/// A corner of a glyph quad, with the position in `xy` and the texture
+/// coordinates in `zw`
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct TextVertex([f32; 4]);
+
+unsafe impl Vertex for TextVertex {
+    const ATTRIBUTES: &'static [VertexAttribute] = &[VertexAttribute {
+        location: 0,
+        locations: 1,
+        components: 4,
+        gl_type: gl::FLOAT,
+        normalized: false,
+        integer: false,
+        offset: 0,
+    }];
+}
+
 struct Character {
     pub texture: REDTexture,
     pub size: Vector2i,
@@ -23,17 +43,33 @@ struct Character {
 
 impl FontContext {
 
+    #[allow(clippy::result_unit_err)]
     pub fn new_from_path(path: &str, pixel_height: u32) -> Result<FontContext, ()> {
         let lib = ft::Library::init().unwrap();
-        Self::new(lib.new_face(path, 0).unwrap(), pixel_height)
+        Self::new(lib.new_face(path, 0).unwrap(), pixel_height, None)
     }
 
+    #[allow(clippy::result_unit_err)]
     pub fn new_from_buffer(buffer: &[u8], pixel_height: u32) -> Result<FontContext, ()> {
         let lib = ft::Library::init().unwrap();
-        Self::new(lib.new_memory_face(buffer.to_vec(), 0).unwrap(), pixel_height)
+        Self::new(lib.new_memory_face(buffer.to_vec(), 0).unwrap(), pixel_height, None)
+    }
+
+    /// Same as `new_from_path`, but loads the text program from `cache`
+    #[allow(clippy::result_unit_err)]
+    pub fn new_from_path_cached(path: &str, pixel_height: u32, cache: &ProgramCache) -> Result<FontContext, ()> {
+        let lib = ft::Library::init().unwrap();
+        Self::new(lib.new_face(path, 0).unwrap(), pixel_height, Some(cache))
+    }
+
+    /// Same as `new_from_buffer`, but loads the text program from `cache`
+    #[allow(clippy::result_unit_err)]
+    pub fn new_from_buffer_cached(buffer: &[u8], pixel_height: u32, cache: &ProgramCache) -> Result<FontContext, ()> {
+        let lib = ft::Library::init().unwrap();
+        Self::new(lib.new_memory_face(buffer.to_vec(), 0).unwrap(), pixel_height, Some(cache))
     }
 
-    fn new(face: freetype::Face, pixel_height: u32) -> Result<FontContext, ()> {
+    fn new(face: freetype::Face, pixel_height: u32, cache: Option<&ProgramCache>) -> Result<FontContext, ()> {
 
         face.set_pixel_sizes(0, pixel_height).unwrap();
 
@@ -53,13 +89,8 @@ impl FontContext {
                 glyph.bitmap().rows() as usize,
             );
 
-            texture.bind();
-            unsafe {
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-            }
+            // glyphs have no mipmaps, and shouldn't bleed into each other
+            texture.set_sampler(&SamplerDesc::default().wrap(Wrap::ClampToEdge).filter(Filter::Linear));
 
             gl_panic!();
 
@@ -74,32 +105,23 @@ impl FontContext {
 
         let text_vao = VertexArray::new();
         let text_vbo = ArrayBuffer::new();
-        text_vao.bind();
-        text_vbo.bind();
-        text_vbo.dynamic_draw_data(&[0_f32; 6 * 4]);
-        unsafe {
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(
-                0,
-                4,
-                gl::FLOAT,
-                gl::FALSE,
-                (4_usize * ::std::mem::size_of::<f32>()) as gl::types::GLsizei,
-                0 as *const gl::types::GLvoid,
-            );
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            text_vao.unbind();
-        }
+        text_vbo.dynamic_draw_data(&[TextVertex([0_f32; 4]); 6]);
+        text_vao.configure_attributes::<TextVertex>(&text_vbo);
+        text_vao.unbind();
 
         gl_panic!();
 
+        let text_program = ProgramBuilder::new()
+            .vertex(include_str!("res/text_vertex_shader.glsl"))
+            .fragment(include_str!("res/text_frag_shader.glsl"));
+
         Ok(FontContext {
             map,
             pixel_height,
-            text_shader: Program::from_src(
-                include_str!("res/text_vertex_shader.glsl"),
-                include_str!("res/text_frag_shader.glsl")
-            ).unwrap(),
+            text_shader: match cache {
+                Some(cache) => cache.build(&text_program),
+                None => text_program.build(),
+            }.unwrap(),
             text_vao,
             text_vbo,
         })
@@ -127,13 +149,13 @@ impl FontContext {
 
     pub fn render_text_center_justified(
         &self,
-        text: &String, 
+        text: &str, 
         center_pos: Vec2, 
         scale: f32, 
         projection: &Mat4, 
         clr: &[f32; 3]) 
         -> Rect {
-        let text_width = self.text_width(text.as_str(), scale);
+        let text_width = self.text_width(text, scale);
 
         let mut corner_pos = center_pos;
         corner_pos.x -= 0.5_f32 * text_width;
@@ -150,16 +172,16 @@ impl FontContext {
         -> Rect {
         self.text_shader.set_used();
         self.text_shader
-            .set_3float("textColor".to_string(), clr.clone());
+            .set_3float("textColor".to_string(), *clr);
 
         self.text_shader
-            .set_uniform_glm_m4("projection".to_string(), &projection);    
+            .set_uniform_glm_m4("projection".to_string(), projection);    
 
         unsafe {
             gl::ActiveTexture(gl::TEXTURE0);
             self.text_vao.bind();
 
-            let mut _pos = pos.clone();
+            let mut _pos = pos;
 
             // iterate through characters
             for c in text.chars() {
@@ -172,36 +194,17 @@ impl FontContext {
                 let h = (ctex.size.y as f32) * scale;
 
                 let vertices = [
-                    xpos,
-                    ypos + h,
-                    0_f32,
-                    0_f32,
-                    xpos,
-                    ypos,
-                    0_f32,
-                    1_f32,
-                    xpos + w,
-                    ypos,
-                    1_f32,
-                    1_f32,
-                    xpos,
-                    ypos + h,
-                    0_f32,
-                    0_f32,
-                    xpos + w,
-                    ypos,
-                    1_f32,
-                    1_f32,
-                    xpos + w,
-                    ypos + h,
-                    1_f32,
-                    0_f32,
+                    TextVertex([xpos, ypos + h, 0_f32, 0_f32]),
+                    TextVertex([xpos, ypos, 0_f32, 1_f32]),
+                    TextVertex([xpos + w, ypos, 1_f32, 1_f32]),
+                    TextVertex([xpos, ypos + h, 0_f32, 0_f32]),
+                    TextVertex([xpos + w, ypos, 1_f32, 1_f32]),
+                    TextVertex([xpos + w, ypos + h, 1_f32, 0_f32]),
                 ];
 
                 ctex.texture.bind();
                 self.text_vbo.update_data(&vertices);
-                self.text_vbo.unbind();
-                gl::DrawArrays(gl::TRIANGLES, 0, 6);
+                gl::DrawArrays(gl::TRIANGLES, 0, self.text_vbo.len() as gl::types::GLsizei);
 
                 gl_panic!();
 
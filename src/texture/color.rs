@@ -9,6 +9,8 @@ pub struct SerializedRGB<T> {
     pub b: T,
 }
 
+unsafe impl<T: crate::buffer::Pod> crate::buffer::Pod for SerializedRGB<T> {}
+
 pub type ColorF32 = SerializedRGB<f32>;
 pub type ColorU8 = SerializedRGB<u8>;
 
@@ -72,8 +74,14 @@ impl<T> From<&[T;3]> for SerializedRGB<T> where T:Copy {
     }
 }
 
-impl<T> Into<[T;3]> for SerializedRGB<T>  {
-    fn into(self) -> [T;3] {
-        [self.r, self.g, self.b]
+impl<T> From<SerializedRGB<T>> for [T;3] {
+    fn from(value: SerializedRGB<T>) -> Self {
+        [value.r, value.g, value.b]
     }
-}
\ No newline at end of file
+}
+
+impl From<ColorF32> for nalgebra_glm::Vec3 {
+    fn from(value: ColorF32) -> Self {
+        nalgebra_glm::Vec3::new(value.r, value.g, value.b)
+    }
+}
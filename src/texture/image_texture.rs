@@ -0,0 +1,236 @@
+//! Loading textures straight from image files, picking a texture format that
+//! fits the image's channels and bit depth
+
+use std::os::raw::c_void;
+use std::path::Path;
+
+use image::{ColorType, DynamicImage};
+
+use super::*;
+
+/// How an image is turned into a texture
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Images store their top row first while GL expects the bottom row
+    /// first, so flipping makes texture coordinate (0, 0) the bottom left
+    pub flip_vertically: bool,
+    pub mipmaps: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions {
+            flip_vertically: true,
+            mipmaps: true,
+        }
+    }
+}
+
+/// A texture loaded from an image, in the format that matches the image.
+/// Gray with alpha is expanded to RGBA, and gray textures are swizzled to
+/// sample as `(l, l, l, 1)` rather than red.
+///
+/// The texture repeats and filters linearly, with trilinear filtering when
+/// mipmaps were generated. Use `set_sampler` to change that
+pub enum ImageTexture {
+    Gray(REDTexture),
+    Rgb(U8RGBTexture),
+    Rgba(U8RGBATexture),
+    Gray16(R16Texture),
+    Rgb16(U16RGBTexture),
+    Rgba16(U16RGBATexture),
+    RgbF32(RGBTexture),
+    RgbaF32(RGBA32FTexture),
+}
+
+/// The `ImageTexture` variant an image is loaded as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TextureKind {
+    Gray,
+    Rgb,
+    Rgba,
+    Gray16,
+    Rgb16,
+    Rgba16,
+    RgbF32,
+    RgbaF32,
+}
+
+/// Picks the texture format for an image's color type, converting the
+/// types without a matching format to the closest one that keeps their
+/// precision and alpha
+fn texture_kind(color: ColorType) -> TextureKind {
+    match color {
+        ColorType::L8 => TextureKind::Gray,
+        ColorType::Rgb8 => TextureKind::Rgb,
+        ColorType::L16 => TextureKind::Gray16,
+        ColorType::Rgb16 => TextureKind::Rgb16,
+        ColorType::Rgba16 | ColorType::La16 => TextureKind::Rgba16,
+        ColorType::Rgb32F => TextureKind::RgbF32,
+        ColorType::Rgba32F => TextureKind::RgbaF32,
+        _ => TextureKind::Rgba,
+    }
+}
+
+impl ImageTexture {
+    pub fn from_path<P>(path: P, options: &LoadOptions) -> Result<Self, String>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|err| format!("Couldn't load image {}: {}", path.display(), err))?;
+        Ok(Self::from_image(image, options))
+    }
+
+    /// Decodes an image file already in memory, e.g. from `include_bytes!`
+    pub fn from_bytes(bytes: &[u8], options: &LoadOptions) -> Result<Self, String> {
+        let image = image::load_from_memory(bytes)
+            .map_err(|err| format!("Couldn't decode image: {}", err))?;
+        Ok(Self::from_image(image, options))
+    }
+
+    pub fn from_image(image: DynamicImage, options: &LoadOptions) -> Self {
+        let image = if options.flip_vertically {
+            image.flipv()
+        } else {
+            image
+        };
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let mipmaps = options.mipmaps;
+
+        // the `into_*` conversions don't copy images already of that type
+        let texture = match texture_kind(image.color()) {
+            TextureKind::Gray => {
+                let buf = image.into_luma8();
+                ImageTexture::Gray(Texture::upload(raw(&buf), width, height, mipmaps))
+            }
+            TextureKind::Rgb => {
+                let buf = image.into_rgb8();
+                ImageTexture::Rgb(Texture::upload(raw(&buf), width, height, mipmaps))
+            }
+            TextureKind::Rgba => {
+                let buf = image.into_rgba8();
+                ImageTexture::Rgba(Texture::upload(raw(&buf), width, height, mipmaps))
+            }
+            TextureKind::Gray16 => {
+                let buf = image.into_luma16();
+                ImageTexture::Gray16(Texture::upload(raw(&buf), width, height, mipmaps))
+            }
+            TextureKind::Rgb16 => {
+                let buf = image.into_rgb16();
+                ImageTexture::Rgb16(Texture::upload(raw(&buf), width, height, mipmaps))
+            }
+            TextureKind::Rgba16 => {
+                let buf = image.into_rgba16();
+                ImageTexture::Rgba16(Texture::upload(raw(&buf), width, height, mipmaps))
+            }
+            TextureKind::RgbF32 => {
+                let buf = image.into_rgb32f();
+                ImageTexture::RgbF32(Texture::upload(raw(&buf), width, height, mipmaps))
+            }
+            TextureKind::RgbaF32 => {
+                let buf = image.into_rgba32f();
+                ImageTexture::RgbaF32(Texture::upload(raw(&buf), width, height, mipmaps))
+            }
+        };
+
+        if let ImageTexture::Gray(_) | ImageTexture::Gray16(_) = texture {
+            let swizzle = [gl::RED, gl::RED, gl::RED, gl::ONE].map(|c| c as gl::types::GLint);
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_2D, texture.id());
+                gl::TexParameteriv(gl::TEXTURE_2D, gl::TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr());
+            }
+        }
+
+        let sampler = SamplerDesc::default().wrap(Wrap::Repeat);
+        texture.set_sampler(&if mipmaps {
+            sampler.mipmaps(Filter::Linear)
+        } else {
+            sampler
+        });
+        texture
+    }
+
+    pub fn id(&self) -> gl::types::GLuint {
+        match self {
+            ImageTexture::Gray(t) => t.id,
+            ImageTexture::Rgb(t) => t.id,
+            ImageTexture::Rgba(t) => t.id,
+            ImageTexture::Gray16(t) => t.id,
+            ImageTexture::Rgb16(t) => t.id,
+            ImageTexture::Rgba16(t) => t.id,
+            ImageTexture::RgbF32(t) => t.id,
+            ImageTexture::RgbaF32(t) => t.id,
+        }
+    }
+
+    pub fn size(&self) -> (i32, i32) {
+        match self {
+            ImageTexture::Gray(t) => (t.width, t.height),
+            ImageTexture::Rgb(t) => (t.width, t.height),
+            ImageTexture::Rgba(t) => (t.width, t.height),
+            ImageTexture::Gray16(t) => (t.width, t.height),
+            ImageTexture::Rgb16(t) => (t.width, t.height),
+            ImageTexture::Rgba16(t) => (t.width, t.height),
+            ImageTexture::RgbF32(t) => (t.width, t.height),
+            ImageTexture::RgbaF32(t) => (t.width, t.height),
+        }
+    }
+
+    pub fn set_sampler(&self, desc: &SamplerDesc) {
+        match self {
+            ImageTexture::Gray(t) => t.set_sampler(desc),
+            ImageTexture::Rgb(t) => t.set_sampler(desc),
+            ImageTexture::Rgba(t) => t.set_sampler(desc),
+            ImageTexture::Gray16(t) => t.set_sampler(desc),
+            ImageTexture::Rgb16(t) => t.set_sampler(desc),
+            ImageTexture::Rgba16(t) => t.set_sampler(desc),
+            ImageTexture::RgbF32(t) => t.set_sampler(desc),
+            ImageTexture::RgbaF32(t) => t.set_sampler(desc),
+        }
+    }
+}
+
+impl TextureBinding for ImageTexture {
+    fn attach_to_unit(&self, tex_unit: gl::types::GLuint) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + tex_unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.id());
+        }
+    }
+}
+
+fn raw<P>(buf: &image::ImageBuffer<P, Vec<P::Subpixel>>) -> *const c_void
+where
+    P: image::Pixel,
+{
+    buf.as_raw().as_ptr() as *const c_void
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matching_formats() {
+        assert_eq!(texture_kind(ColorType::L8), TextureKind::Gray);
+        assert_eq!(texture_kind(ColorType::Rgb8), TextureKind::Rgb);
+        assert_eq!(texture_kind(ColorType::Rgba8), TextureKind::Rgba);
+        assert_eq!(texture_kind(ColorType::L16), TextureKind::Gray16);
+        assert_eq!(texture_kind(ColorType::Rgb16), TextureKind::Rgb16);
+        assert_eq!(texture_kind(ColorType::Rgba16), TextureKind::Rgba16);
+    }
+
+    #[test]
+    fn gray_with_alpha_is_expanded() {
+        assert_eq!(texture_kind(ColorType::La8), TextureKind::Rgba);
+        assert_eq!(texture_kind(ColorType::La16), TextureKind::Rgba16);
+    }
+
+    #[test]
+    fn float_images_stay_float() {
+        assert_eq!(texture_kind(ColorType::Rgb32F), TextureKind::RgbF32);
+        assert_eq!(texture_kind(ColorType::Rgba32F), TextureKind::RgbaF32);
+    }
+}
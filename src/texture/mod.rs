@@ -1,7 +1,11 @@
+mod image_texture;
 mod rgb_image;
 mod rgb_texture;
 mod color;
+mod sampler;
 
 pub use rgb_image::*;
 pub use rgb_texture::*;
-pub use color::*;
\ No newline at end of file
+pub use color::*;
+pub use sampler::{CompareFunc, Filter, Sampler, SamplerDesc, Wrap};
+pub use image_texture::{ImageTexture, LoadOptions};
@@ -1,4 +1,5 @@
-use crate::glchk;
+use crate::{buffer::{packed_tightly, Pod}, glchk, PendingRead};
+use super::SamplerDesc;
 use gl;
 use std::os::raw::c_void;
 
@@ -14,6 +15,16 @@ pub trait TextureType<TData> {
     const TEXTURE_WRAP_T: gl::types::GLenum;
     const TEXTURE_MIN_FILTER: gl::types::GLenum;
     const TEXTURE_MAG_FILTER: gl::types::GLenum;
+    /// Whether mipmaps are generated when the texture is made with data.
+    /// Integer, depth and stencil formats, and rectangle textures, can't
+    /// have them generated
+    const MIPMAPS: bool = true;
+}
+
+/// Anything that can be bound to a texture unit, so textures of different
+/// types can be stored together
+pub trait TextureBinding {
+    fn attach_to_unit(&self, tex_unit: gl::types::GLuint);
 }
 
 pub struct Texture<TTex,TData>
@@ -33,13 +44,30 @@ pub type RGBTextureRect = Texture<TextureTypeRGBRect, [f32;3]>;
 pub type RGBTexture = Texture<TextureTypeRGB, [f32;3]>; 
 pub type U8RGBTexture = Texture<TextureTypeU8RGB, [u8;3]>;
 pub type U8RGBATexture = Texture<TextureTypeU8RGBA, [u8;4]>;
-pub type F32Texture = Texture<TextureTypeRGB, f32>;
+pub type F32Texture = Texture<TextureTypeF32, f32>;
 pub type I32Texture = Texture<TextureTypeI32, i32>;
 pub type REDTexture = Texture<TextureTypeRed, u8>;
+pub type R16Texture = Texture<TextureTypeR16, u16>;
+pub type U16RGBTexture = Texture<TextureTypeU16RGB, [u16;3]>;
+pub type U16RGBATexture = Texture<TextureTypeU16RGBA, [u16;4]>;
+pub type U8RGTexture = Texture<TextureTypeU8RG, [u8;2]>;
+pub type F32RGTexture = Texture<TextureTypeF32RG, [f32;2]>;
+pub type RGBA16FTexture = Texture<TextureTypeRGBA16F, [f32;4]>;
+pub type RGBA32FTexture = Texture<TextureTypeRGBA32F, [f32;4]>;
+pub type U32Texture = Texture<TextureTypeU32, u32>;
+pub type U32RGBATexture = Texture<TextureTypeU32RGBA, [u32;4]>;
+pub type SRGBTexture = Texture<TextureTypeSRGB, [u8;3]>;
+pub type SRGBATexture = Texture<TextureTypeSRGBA, [u8;4]>;
+pub type Depth16Texture = Texture<TextureTypeDepth16, u16>;
+pub type Depth24Texture = Texture<TextureTypeDepth24, u32>;
+pub type Depth32FTexture = Texture<TextureTypeDepth32F, f32>;
+pub type Stencil8Texture = Texture<TextureTypeStencil8, u8>;
+pub type Depth24Stencil8Texture = Texture<TextureTypeDepth24Stencil8, u32>;
+pub type Depth32FStencil8Texture = Texture<TextureTypeDepth32FStencil8, [u32;2]>;
 
 pub struct TextureTypeRGBRect;
 impl TextureType<[f32;3]> for TextureTypeRGBRect {
-    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGB4;
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGB32F;
     const TEXTURE_TYPE: gl::types::GLuint = gl::RGB;
     const DATA_TYPE: gl::types::GLenum = gl::FLOAT;
     /// Is texture rectangle just b/c the only place I used this was 
@@ -50,11 +78,12 @@ impl TextureType<[f32;3]> for TextureTypeRGBRect {
     const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
     const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::LINEAR;
     const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
+    const MIPMAPS: bool = false;
 }
 
 pub struct TextureTypeRGB;
 impl TextureType<[f32;3]> for TextureTypeRGB {
-    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGB4;
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGB32F;
     const TEXTURE_TYPE: gl::types::GLuint = gl::RGB;
     const DATA_TYPE: gl::types::GLenum = gl::FLOAT;
     const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
@@ -66,8 +95,8 @@ impl TextureType<[f32;3]> for TextureTypeRGB {
 
 pub struct TextureTypeU8RGB;
 impl TextureType<[u8;3]> for TextureTypeU8RGB {
-    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGBA;
-    const TEXTURE_TYPE: gl::types::GLuint = gl::RGBA;
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGB8;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RGB;
     const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
     const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
     const TEXTURE_WRAP_S: gl::types::GLenum = gl::REPEAT;
@@ -78,7 +107,7 @@ impl TextureType<[u8;3]> for TextureTypeU8RGB {
 
 pub struct TextureTypeU8RGBA;
 impl TextureType<[u8;4]> for TextureTypeU8RGBA {
-    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGBA;
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGBA8;
     const TEXTURE_TYPE: gl::types::GLuint = gl::RGBA;
     const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
     const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
@@ -88,6 +117,7 @@ impl TextureType<[u8;4]> for TextureTypeU8RGBA {
     const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
 }
 
+/// Integer textures can't be filtered, so they always use `NEAREST`
 pub struct TextureTypeI32;
 impl TextureType<i32> for TextureTypeI32 {
     const INTERNAL_FORMAT: gl::types::GLuint = gl::R32I;
@@ -96,13 +126,14 @@ impl TextureType<i32> for TextureTypeI32 {
     const TARGET: gl::types::GLenum = gl::TEXTURE_RECTANGLE;
     const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
     const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
-    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::LINEAR;
-    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::NEAREST;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::NEAREST;
+    const MIPMAPS: bool = false;
 }
 
 pub struct TextureTypeRed;
 impl TextureType<u8> for TextureTypeRed {
-    const INTERNAL_FORMAT: gl::types::GLuint = gl::RED;
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::R8;
     const TEXTURE_TYPE: gl::types::GLuint = gl::RED;
     const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
     const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
@@ -124,6 +155,232 @@ impl TextureType<f32> for TextureTypeF32 {
     const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
 }
 
+pub struct TextureTypeR16;
+impl TextureType<u16> for TextureTypeR16 {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::R16;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RED;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_SHORT;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::LINEAR;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
+}
+
+pub struct TextureTypeU16RGB;
+impl TextureType<[u16;3]> for TextureTypeU16RGB {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGB16;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RGB;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_SHORT;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::REPEAT;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::REPEAT;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::LINEAR_MIPMAP_LINEAR;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
+}
+
+pub struct TextureTypeU16RGBA;
+impl TextureType<[u16;4]> for TextureTypeU16RGBA {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGBA16;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RGBA;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_SHORT;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::REPEAT;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::REPEAT;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::LINEAR_MIPMAP_LINEAR;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
+}
+
+pub struct TextureTypeU8RG;
+impl TextureType<[u8;2]> for TextureTypeU8RG {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::RG8;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RG;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::LINEAR;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
+}
+
+pub struct TextureTypeF32RG;
+impl TextureType<[f32;2]> for TextureTypeF32RG {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::RG32F;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RG;
+    const DATA_TYPE: gl::types::GLenum = gl::FLOAT;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::LINEAR;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
+}
+
+/// Half floats on the GPU, converted from and to `f32` by GL, which
+/// halves the memory of HDR render targets
+pub struct TextureTypeRGBA16F;
+impl TextureType<[f32;4]> for TextureTypeRGBA16F {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGBA16F;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RGBA;
+    const DATA_TYPE: gl::types::GLenum = gl::FLOAT;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::LINEAR;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
+}
+
+pub struct TextureTypeRGBA32F;
+impl TextureType<[f32;4]> for TextureTypeRGBA32F {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGBA32F;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RGBA;
+    const DATA_TYPE: gl::types::GLenum = gl::FLOAT;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::LINEAR;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
+}
+
+pub struct TextureTypeU32;
+impl TextureType<u32> for TextureTypeU32 {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::R32UI;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RED_INTEGER;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_INT;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::NEAREST;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::NEAREST;
+    const MIPMAPS: bool = false;
+}
+
+pub struct TextureTypeU32RGBA;
+impl TextureType<[u32;4]> for TextureTypeU32RGBA {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::RGBA32UI;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RGBA_INTEGER;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_INT;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::NEAREST;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::NEAREST;
+    const MIPMAPS: bool = false;
+}
+
+/// Colors stored in sRGB, converted to linear when sampled
+pub struct TextureTypeSRGB;
+impl TextureType<[u8;3]> for TextureTypeSRGB {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::SRGB8;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RGB;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::REPEAT;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::REPEAT;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::LINEAR_MIPMAP_LINEAR;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
+}
+
+/// Colors stored in sRGB with linear alpha, converted to linear when
+/// sampled
+pub struct TextureTypeSRGBA;
+impl TextureType<[u8;4]> for TextureTypeSRGBA {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::SRGB8_ALPHA8;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::RGBA;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::REPEAT;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::REPEAT;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::LINEAR_MIPMAP_LINEAR;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::LINEAR;
+}
+
+// Depth and stencil textures are attached to a framebuffer at
+// `gl::DEPTH_ATTACHMENT`, `gl::STENCIL_ATTACHMENT` or
+// `gl::DEPTH_STENCIL_ATTACHMENT`, and are usually made with `Texture::new`
+
+pub struct TextureTypeDepth16;
+impl TextureType<u16> for TextureTypeDepth16 {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::DEPTH_COMPONENT16;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::DEPTH_COMPONENT;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_SHORT;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::NEAREST;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::NEAREST;
+    const MIPMAPS: bool = false;
+}
+
+/// Read back as `u32`s with the depth range mapped onto `0..=u32::MAX`
+pub struct TextureTypeDepth24;
+impl TextureType<u32> for TextureTypeDepth24 {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::DEPTH_COMPONENT24;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::DEPTH_COMPONENT;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_INT;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::NEAREST;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::NEAREST;
+    const MIPMAPS: bool = false;
+}
+
+pub struct TextureTypeDepth32F;
+impl TextureType<f32> for TextureTypeDepth32F {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::DEPTH_COMPONENT32F;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::DEPTH_COMPONENT;
+    const DATA_TYPE: gl::types::GLenum = gl::FLOAT;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::NEAREST;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::NEAREST;
+    const MIPMAPS: bool = false;
+}
+
+/// Needs GL 4.4 to be used as a texture
+pub struct TextureTypeStencil8;
+impl TextureType<u8> for TextureTypeStencil8 {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::STENCIL_INDEX8;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::STENCIL_INDEX;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_BYTE;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::NEAREST;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::NEAREST;
+    const MIPMAPS: bool = false;
+}
+
+/// Depth in the top 24 bits of each `u32` and stencil in the bottom 8
+pub struct TextureTypeDepth24Stencil8;
+impl TextureType<u32> for TextureTypeDepth24Stencil8 {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::DEPTH24_STENCIL8;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::DEPTH_STENCIL;
+    const DATA_TYPE: gl::types::GLenum = gl::UNSIGNED_INT_24_8;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::NEAREST;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::NEAREST;
+    const MIPMAPS: bool = false;
+}
+
+/// The depth as the bits of an `f32` in the first `u32`, and stencil in
+/// the bottom 8 bits of the second
+pub struct TextureTypeDepth32FStencil8;
+impl TextureType<[u32;2]> for TextureTypeDepth32FStencil8 {
+    const INTERNAL_FORMAT: gl::types::GLuint = gl::DEPTH32F_STENCIL8;
+    const TEXTURE_TYPE: gl::types::GLuint = gl::DEPTH_STENCIL;
+    const DATA_TYPE: gl::types::GLenum = gl::FLOAT_32_UNSIGNED_INT_24_8_REV;
+    const TARGET: gl::types::GLenum = gl::TEXTURE_2D;
+    const TEXTURE_WRAP_S: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_WRAP_T: gl::types::GLenum = gl::CLAMP_TO_EDGE;
+    const TEXTURE_MIN_FILTER: gl::types::GLenum = gl::NEAREST;
+    const TEXTURE_MAG_FILTER: gl::types::GLenum = gl::NEAREST;
+    const MIPMAPS: bool = false;
+}
+
 impl Clone for RGBTexture {
     fn clone(&self) -> Self {
         RGBTexture::new_from_data(
@@ -176,33 +433,7 @@ where
     /// that is, maybe we can make specific functions for passing in color data or u32 data
     /// instead of void*
     pub fn new_from_data(data: &[TData], width: usize, height: usize) -> Self {
-        let target = TTex::TARGET;
-
-        let id = Self::create_and_set_gl_parameters();
-        unsafe {
-            gl::TexImage2D(
-                target,
-                0,
-                TTex::INTERNAL_FORMAT as i32,
-                width as i32,
-                height as i32,
-                0,
-                TTex::TEXTURE_TYPE,
-                TTex::DATA_TYPE,
-                data.as_ptr() as *const c_void,
-            );
-
-            gl::GenerateMipmap(gl::TEXTURE_2D);
-        }
-
-        Texture {
-            id,
-            width: width as i32,
-            height: height as i32,
-            target,
-            attach_point: 0,
-            _hack: std::marker::PhantomData,
-        }
+        Self::upload(data.as_ptr() as *const c_void, width, height, true)
     }
     /// This allocates a texture on the video card of the given size
     /// containing the data.
@@ -211,10 +442,20 @@ where
     /// that is, maybe we can make specific functions for passing in color data or u32 data
     /// instead of void*
     pub fn new_from_ptr(data: *const TData, width: usize, height: usize) -> Self {
+        Self::upload(data as *const c_void, width, height, true)
+    }
+
+    /// Uploads tightly packed rows of pixels, so 1 and 3 byte pixels work
+    /// whatever the row length. Mipmaps are only generated if `TTex` can
+    /// have them
+    pub(crate) fn upload(data: *const c_void, width: usize, height: usize, mipmaps: bool) -> Self {
         let target = TTex::TARGET;
 
         let id = Self::create_and_set_gl_parameters();
         unsafe {
+            let mut alignment: gl::types::GLint = 0;
+            gl::GetIntegerv(gl::UNPACK_ALIGNMENT, &mut alignment);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
             gl::TexImage2D(
                 target,
                 0,
@@ -224,10 +465,13 @@ where
                 0,
                 TTex::TEXTURE_TYPE,
                 TTex::DATA_TYPE,
-                data as *const c_void,
+                data,
             );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
 
-            gl::GenerateMipmap(gl::TEXTURE_2D);
+            if mipmaps && TTex::MIPMAPS {
+                gl::GenerateMipmap(target);
+            }
         }
 
         Texture {
@@ -255,7 +499,7 @@ where
     pub fn get_pixel_data(&self) -> Vec<TData> {
         self.bind();
         let mut data = vec![TData::default(); (self.width * self.height) as usize];
-        unsafe {
+        packed_tightly(|| unsafe {
             glchk!(
                 gl::GetTexImage(TTex::TARGET,
                     0,
@@ -264,7 +508,7 @@ where
                     data.as_mut_ptr() as *mut gl::types::GLvoid,
                 );
             );
-        }
+        });
 
         data
     }
@@ -284,8 +528,15 @@ where
         //self.attach_point = gl::TEXTURE0 + tex_unit;
     }
 
-    /// TODO impl some mech for this to be cusomized per texture, because
-    /// for instance a character texture needs specific settings
+    /// Overrides the wrap and filter modes from the texture type, and the
+    /// rest of the sampler state, for this texture
+    pub fn set_sampler(&self, desc: &SamplerDesc) {
+        self.bind();
+        desc.apply_to_texture(TTex::TARGET);
+    }
+
+    /// Starts the texture off with the texture type's wrap and filter modes,
+    /// which `set_sampler` can change per texture
     fn create_and_set_gl_parameters() -> gl::types::GLuint {
 	let mut id: gl::types::GLuint = 0;
 	let target = TTex::TARGET;
@@ -302,6 +553,37 @@ where
     }
 }
 
+impl<TTex,TData> Texture<TTex,TData>
+where
+    TTex: TextureType<TData>,
+	TData: Default + Pod
+{
+    /// Starts copying the texture into a pixel buffer, without waiting
+    /// for the GPU like `get_pixel_data` does
+    pub fn read_async(&self) -> PendingRead<TData> {
+        self.bind();
+        PendingRead::new(self.width as usize, self.height as usize, || unsafe {
+            gl::GetTexImage(
+                TTex::TARGET,
+                0,
+                TTex::TEXTURE_TYPE,
+                TTex::DATA_TYPE,
+                std::ptr::null_mut(),
+            );
+        })
+    }
+}
+
+impl<TTex,TData> TextureBinding for Texture<TTex,TData>
+where
+    TTex: TextureType<TData>,
+	TData: Default + Clone
+{
+    fn attach_to_unit(&self, tex_unit: gl::types::GLuint) {
+        Texture::attach_to_unit(self, tex_unit)
+    }
+}
+
 impl<TTex,TData> Drop for Texture<TTex,TData>
 where
     TTex: TextureType<TData>,
@@ -313,3 +595,110 @@ where
         }
     }
 }
+
+/// Every alias is checked to pair its internal format with a pixel format
+/// and data type that describe `TData` exactly. Uploading and reading back
+/// through `get_pixel_data` needs a GL context, which tests don't have
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::marker::PhantomData;
+
+    fn channels(format: gl::types::GLenum) -> usize {
+        match format {
+            gl::RED | gl::RED_INTEGER | gl::DEPTH_COMPONENT | gl::STENCIL_INDEX => 1,
+            // packed into a single value
+            gl::DEPTH_STENCIL => 1,
+            gl::RG | gl::RG_INTEGER => 2,
+            gl::RGB | gl::RGB_INTEGER => 3,
+            gl::RGBA | gl::RGBA_INTEGER => 4,
+            _ => panic!("unknown pixel format 0x{:x}", format),
+        }
+    }
+
+    fn component_size(data_type: gl::types::GLenum) -> usize {
+        match data_type {
+            gl::UNSIGNED_BYTE | gl::BYTE => 1,
+            gl::UNSIGNED_SHORT | gl::SHORT => 2,
+            gl::UNSIGNED_INT | gl::INT | gl::FLOAT | gl::UNSIGNED_INT_24_8 => 4,
+            gl::FLOAT_32_UNSIGNED_INT_24_8_REV => 8,
+            _ => panic!("unknown data type 0x{:x}", data_type),
+        }
+    }
+
+    /// Channels of the internal format, and whether it's read as integers
+    fn internal_format(internal: gl::types::GLuint) -> (usize, bool) {
+        match internal {
+            gl::R8 | gl::R16 | gl::R32F => (1, false),
+            gl::DEPTH_COMPONENT16 | gl::DEPTH_COMPONENT24 | gl::DEPTH_COMPONENT32F => (1, false),
+            gl::STENCIL_INDEX8 | gl::DEPTH24_STENCIL8 | gl::DEPTH32F_STENCIL8 => (1, false),
+            gl::R32I | gl::R32UI => (1, true),
+            gl::RG8 | gl::RG32F => (2, false),
+            gl::RGB8 | gl::RGB16 | gl::RGB32F | gl::SRGB8 => (3, false),
+            gl::RGBA8 | gl::RGBA16 | gl::RGBA16F | gl::RGBA32F | gl::SRGB8_ALPHA8 => (4, false),
+            gl::RGBA32UI => (4, true),
+            _ => panic!("unknown internal format 0x{:x}", internal),
+        }
+    }
+
+    fn check<TTex, TData>(_: PhantomData<Texture<TTex, TData>>)
+    where
+        TTex: TextureType<TData>,
+        TData: Default + Clone,
+    {
+        assert_eq!(
+            std::mem::size_of::<TData>(),
+            channels(TTex::TEXTURE_TYPE) * component_size(TTex::DATA_TYPE),
+            "pixel format doesn't match the size of {}",
+            std::any::type_name::<TData>()
+        );
+
+        let (internal_channels, integer) = internal_format(TTex::INTERNAL_FORMAT);
+        assert_eq!(internal_channels, channels(TTex::TEXTURE_TYPE));
+
+        let integer_format = matches!(
+            TTex::TEXTURE_TYPE,
+            gl::RED_INTEGER | gl::RG_INTEGER | gl::RGB_INTEGER | gl::RGBA_INTEGER
+        );
+        assert_eq!(integer, integer_format, "integer formats need *_INTEGER pixel formats");
+        if integer {
+            assert_eq!(TTex::TEXTURE_MIN_FILTER, gl::NEAREST, "integer textures can't be filtered");
+            assert_eq!(TTex::TEXTURE_MAG_FILTER, gl::NEAREST, "integer textures can't be filtered");
+            assert!(!TTex::MIPMAPS, "integer textures can't generate mipmaps");
+        }
+        if matches!(TTex::TEXTURE_TYPE, gl::DEPTH_COMPONENT | gl::STENCIL_INDEX | gl::DEPTH_STENCIL) {
+            assert!(!TTex::MIPMAPS, "depth and stencil textures can't generate mipmaps");
+        }
+        if TTex::TARGET == gl::TEXTURE_RECTANGLE {
+            assert!(!TTex::MIPMAPS, "rectangle textures can't have mipmaps");
+        }
+    }
+
+    #[test]
+    fn formats_match_data() {
+        check(PhantomData::<RGBTextureRect>);
+        check(PhantomData::<RGBTexture>);
+        check(PhantomData::<U8RGBTexture>);
+        check(PhantomData::<U8RGBATexture>);
+        check(PhantomData::<F32Texture>);
+        check(PhantomData::<I32Texture>);
+        check(PhantomData::<REDTexture>);
+        check(PhantomData::<R16Texture>);
+        check(PhantomData::<U16RGBTexture>);
+        check(PhantomData::<U16RGBATexture>);
+        check(PhantomData::<U8RGTexture>);
+        check(PhantomData::<F32RGTexture>);
+        check(PhantomData::<RGBA16FTexture>);
+        check(PhantomData::<RGBA32FTexture>);
+        check(PhantomData::<U32Texture>);
+        check(PhantomData::<U32RGBATexture>);
+        check(PhantomData::<SRGBTexture>);
+        check(PhantomData::<SRGBATexture>);
+        check(PhantomData::<Depth16Texture>);
+        check(PhantomData::<Depth24Texture>);
+        check(PhantomData::<Depth32FTexture>);
+        check(PhantomData::<Stencil8Texture>);
+        check(PhantomData::<Depth24Stencil8Texture>);
+        check(PhantomData::<Depth32FStencil8Texture>);
+    }
+}
@@ -0,0 +1,358 @@
+//! Sampler state chosen at runtime, either set on a texture or held in a
+//! sampler object that overrides the state of whatever texture is bound to
+//! the same unit
+
+use gl;
+use std::cell::Cell;
+
+/// `GL_TEXTURE_MAX_ANISOTROPY`, core in 4.6 and the same value as the
+/// extension, but newer than the bindings
+const TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FE;
+/// `GL_MAX_TEXTURE_MAX_ANISOTROPY`, likewise
+const MAX_TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FF;
+
+thread_local! {
+    /// The context's anisotropy limit, `Some(None)` once it's known to be
+    /// unsupported. Contexts are current on one thread, so it's cached per
+    /// thread
+    static MAX_ANISOTROPY: Cell<Option<Option<f32>>> = const { Cell::new(None) };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    ClampToBorder,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+/// The comparison depth textures make against the reference value when
+/// sampled with a shadow sampler
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareFunc {
+    Never,
+    Less,
+    LessEqual,
+    Equal,
+    NotEqual,
+    GreaterEqual,
+    Greater,
+    Always,
+}
+
+/// How a texture is sampled. The default clamps to the edge and filters
+/// linearly without mipmaps
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SamplerDesc {
+    pub wrap_s: Wrap,
+    pub wrap_t: Wrap,
+    pub wrap_r: Wrap,
+    pub min_filter: Filter,
+    pub mag_filter: Filter,
+    /// How mipmap levels are chosen and blended, `None` to only sample the
+    /// base level
+    pub mipmap_filter: Option<Filter>,
+    /// 1.0 turns anisotropic filtering off. Clamped to what the driver
+    /// supports, and ignored if it doesn't support anisotropic filtering
+    pub max_anisotropy: f32,
+    /// Color returned outside the texture with `Wrap::ClampToBorder`
+    pub border_color: [f32; 4],
+    pub lod_bias: f32,
+    /// Turns depth comparison on, for shadow samplers
+    pub compare: Option<CompareFunc>,
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        SamplerDesc {
+            wrap_s: Wrap::ClampToEdge,
+            wrap_t: Wrap::ClampToEdge,
+            wrap_r: Wrap::ClampToEdge,
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            mipmap_filter: None,
+            max_anisotropy: 1.0,
+            border_color: [0.0; 4],
+            lod_bias: 0.0,
+            compare: None,
+        }
+    }
+}
+
+impl SamplerDesc {
+    /// The same wrap mode on every axis
+    pub fn wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap_s = wrap;
+        self.wrap_t = wrap;
+        self.wrap_r = wrap;
+        self
+    }
+
+    /// The same filter for minification and magnification, and for
+    /// choosing mipmap levels if `mipmaps` turned them on. It doesn't turn
+    /// mipmaps on by itself
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.min_filter = filter;
+        self.mag_filter = filter;
+        if self.mipmap_filter.is_some() {
+            self.mipmap_filter = Some(filter);
+        }
+        self
+    }
+
+    pub fn mipmaps(mut self, filter: Filter) -> Self {
+        self.mipmap_filter = Some(filter);
+        self
+    }
+
+    pub fn anisotropy(mut self, max_anisotropy: f32) -> Self {
+        self.max_anisotropy = max_anisotropy;
+        self
+    }
+
+    /// Calls `int` and `float` with every parameter, so the same state can
+    /// go to `glTexParameter*` or `glSamplerParameter*`. `anisotropy_limit`
+    /// is the driver's maximum, `None` if anisotropic filtering isn't
+    /// supported
+    fn apply<I, F, V>(&self, anisotropy_limit: Option<f32>, int: I, float: F, float_vec: V)
+    where
+        I: Fn(gl::types::GLenum, gl::types::GLint),
+        F: Fn(gl::types::GLenum, gl::types::GLfloat),
+        V: Fn(gl::types::GLenum, &[gl::types::GLfloat; 4]),
+    {
+        int(gl::TEXTURE_WRAP_S, wrap_gl(self.wrap_s) as i32);
+        int(gl::TEXTURE_WRAP_T, wrap_gl(self.wrap_t) as i32);
+        int(gl::TEXTURE_WRAP_R, wrap_gl(self.wrap_r) as i32);
+        int(gl::TEXTURE_MIN_FILTER, min_filter_gl(self.min_filter, self.mipmap_filter) as i32);
+        int(gl::TEXTURE_MAG_FILTER, filter_gl(self.mag_filter) as i32);
+        // always written, so going back to 1.0 turns it off again, but it's
+        // an error to set where anisotropy is unsupported
+        if let Some(limit) = anisotropy_limit {
+            float(TEXTURE_MAX_ANISOTROPY, self.max_anisotropy.clamp(1.0, limit));
+        }
+        float_vec(gl::TEXTURE_BORDER_COLOR, &self.border_color);
+        float(gl::TEXTURE_LOD_BIAS, self.lod_bias);
+        match self.compare {
+            Some(func) => {
+                int(gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE as i32);
+                int(gl::TEXTURE_COMPARE_FUNC, compare_gl(func) as i32);
+            }
+            None => int(gl::TEXTURE_COMPARE_MODE, gl::NONE as i32),
+        }
+    }
+
+    /// Sets the state on the texture bound to `target`
+    pub(crate) fn apply_to_texture(&self, target: gl::types::GLenum) {
+        self.apply(
+            anisotropy_limit(),
+            |pname, value| unsafe { gl::TexParameteri(target, pname, value) },
+            |pname, value| unsafe { gl::TexParameterf(target, pname, value) },
+            |pname, value| unsafe { gl::TexParameterfv(target, pname, value.as_ptr()) },
+        );
+    }
+}
+
+/// Asks the driver for its anisotropy limit the first time it's needed
+fn anisotropy_limit() -> Option<f32> {
+    MAX_ANISOTROPY.with(|cached| {
+        if let Some(limit) = cached.get() {
+            return limit;
+        }
+        let limit = anisotropy_supported(gl_version(), &extensions()).then(|| {
+            let mut limit: gl::types::GLfloat = 1.0;
+            unsafe {
+                gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut limit);
+            }
+            limit.max(1.0)
+        });
+        cached.set(Some(limit));
+        limit
+    })
+}
+
+/// Anisotropic filtering is core in 4.6 and an extension before that
+fn anisotropy_supported(version: (i32, i32), extensions: &[String]) -> bool {
+    version >= (4, 6)
+        || extensions.iter().any(|extension| {
+            extension == "GL_EXT_texture_filter_anisotropic"
+                || extension == "GL_ARB_texture_filter_anisotropic"
+        })
+}
+
+fn gl_version() -> (i32, i32) {
+    let (mut major, mut minor) = (0, 0);
+    unsafe {
+        gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+        gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    }
+    (major, minor)
+}
+
+fn extensions() -> Vec<String> {
+    let mut count: gl::types::GLint = 0;
+    unsafe {
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+    }
+    (0..count as gl::types::GLuint)
+        .filter_map(|index| unsafe {
+            let name = gl::GetStringi(gl::EXTENSIONS, index);
+            (!name.is_null()).then(|| {
+                std::ffi::CStr::from_ptr(name as *const std::ffi::c_char)
+                    .to_string_lossy()
+                    .into_owned()
+            })
+        })
+        .collect()
+}
+
+fn wrap_gl(wrap: Wrap) -> gl::types::GLenum {
+    match wrap {
+        Wrap::Repeat => gl::REPEAT,
+        Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+        Wrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+        Wrap::ClampToBorder => gl::CLAMP_TO_BORDER,
+    }
+}
+
+fn filter_gl(filter: Filter) -> gl::types::GLenum {
+    match filter {
+        Filter::Nearest => gl::NEAREST,
+        Filter::Linear => gl::LINEAR,
+    }
+}
+
+/// GL folds the mipmap filter into the minification filter
+fn min_filter_gl(min: Filter, mipmap: Option<Filter>) -> gl::types::GLenum {
+    match (min, mipmap) {
+        (min, None) => filter_gl(min),
+        (Filter::Nearest, Some(Filter::Nearest)) => gl::NEAREST_MIPMAP_NEAREST,
+        (Filter::Nearest, Some(Filter::Linear)) => gl::NEAREST_MIPMAP_LINEAR,
+        (Filter::Linear, Some(Filter::Nearest)) => gl::LINEAR_MIPMAP_NEAREST,
+        (Filter::Linear, Some(Filter::Linear)) => gl::LINEAR_MIPMAP_LINEAR,
+    }
+}
+
+fn compare_gl(func: CompareFunc) -> gl::types::GLenum {
+    match func {
+        CompareFunc::Never => gl::NEVER,
+        CompareFunc::Less => gl::LESS,
+        CompareFunc::LessEqual => gl::LEQUAL,
+        CompareFunc::Equal => gl::EQUAL,
+        CompareFunc::NotEqual => gl::NOTEQUAL,
+        CompareFunc::GreaterEqual => gl::GEQUAL,
+        CompareFunc::Greater => gl::GREATER,
+        CompareFunc::Always => gl::ALWAYS,
+    }
+}
+
+/// A sampler object. While bound to a texture unit, its state is used
+/// instead of the state of the texture bound there, so one texture can be
+/// sampled in different ways
+pub struct Sampler {
+    pub id: gl::types::GLuint,
+}
+
+impl Sampler {
+    pub fn new(desc: &SamplerDesc) -> Sampler {
+        let mut id: gl::types::GLuint = 0;
+        unsafe {
+            gl::GenSamplers(1, &mut id);
+        }
+        let sampler = Sampler { id };
+        sampler.set(desc);
+        sampler
+    }
+
+    pub fn set(&self, desc: &SamplerDesc) {
+        let id = self.id;
+        desc.apply(
+            anisotropy_limit(),
+            |pname, value| unsafe { gl::SamplerParameteri(id, pname, value) },
+            |pname, value| unsafe { gl::SamplerParameterf(id, pname, value) },
+            |pname, value| unsafe { gl::SamplerParameterfv(id, pname, value.as_ptr()) },
+        );
+    }
+
+    pub fn bind(&self, tex_unit: gl::types::GLuint) {
+        unsafe {
+            gl::BindSampler(tex_unit, self.id);
+        }
+    }
+
+    /// Goes back to sampling with the state of the texture bound to
+    /// `tex_unit`
+    pub fn unbind(&self, tex_unit: gl::types::GLuint) {
+        unsafe {
+            gl::BindSampler(tex_unit, 0);
+        }
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteSamplers(1, &self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mipmap_filter_folds_into_min_filter() {
+        assert_eq!(min_filter_gl(Filter::Linear, None), gl::LINEAR);
+        assert_eq!(min_filter_gl(Filter::Linear, Some(Filter::Linear)), gl::LINEAR_MIPMAP_LINEAR);
+        assert_eq!(min_filter_gl(Filter::Nearest, Some(Filter::Linear)), gl::NEAREST_MIPMAP_LINEAR);
+    }
+
+    fn anisotropy_written(desc: &SamplerDesc, limit: Option<f32>) -> Option<f32> {
+        let written = Cell::new(None);
+        desc.apply(
+            limit,
+            |_, _| {},
+            |pname, value| {
+                if pname == TEXTURE_MAX_ANISOTROPY {
+                    written.set(Some(value));
+                }
+            },
+            |_, _| {},
+        );
+        written.get()
+    }
+
+    #[test]
+    fn anisotropy_is_always_written_when_supported() {
+        let desc = SamplerDesc::default();
+        assert_eq!(anisotropy_written(&desc, Some(16.0)), Some(1.0));
+        assert_eq!(anisotropy_written(&desc.anisotropy(8.0), Some(16.0)), Some(8.0));
+        assert_eq!(anisotropy_written(&desc.anisotropy(32.0), Some(16.0)), Some(16.0));
+        assert_eq!(anisotropy_written(&desc.anisotropy(8.0), None), None);
+    }
+
+    #[test]
+    fn anisotropy_is_core_or_an_extension() {
+        let ext = vec!["GL_EXT_texture_filter_anisotropic".to_string()];
+        let arb = vec!["GL_ARB_texture_filter_anisotropic".to_string()];
+        let other = vec!["GL_ARB_debug_output".to_string()];
+        assert!(anisotropy_supported((4, 6), &[]));
+        assert!(anisotropy_supported((4, 5), &ext));
+        assert!(anisotropy_supported((3, 3), &arb));
+        assert!(!anisotropy_supported((4, 5), &other));
+    }
+
+    #[test]
+    fn filter_keeps_mipmaps_off() {
+        let desc = SamplerDesc::default().filter(Filter::Nearest);
+        assert_eq!(desc.mipmap_filter, None);
+        let desc = SamplerDesc::default().mipmaps(Filter::Linear).filter(Filter::Nearest);
+        assert_eq!(desc.mipmap_filter, Some(Filter::Nearest));
+    }
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use render_gl::buffer::StreamElementArrayBuffer;
+use render_gl::{ElementArrayBuffer, VertexArray};
+
+fn element_binding() -> gl::types::GLint {
+    let mut buffer: gl::types::GLint = -1;
+    unsafe { gl::GetIntegerv(gl::ELEMENT_ARRAY_BUFFER_BINDING, &mut buffer) };
+    buffer
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn uploads_leave_the_bound_vertex_arrays_indices_alone() {
+    let _gl = common::headless();
+    let vao = VertexArray::new();
+    let indices = ElementArrayBuffer::<u32>::new();
+    indices.static_draw_data(&[0, 1, 2]);
+    vao.bind();
+    indices.bind();
+    let bound = indices.id() as i32;
+
+    let other = ElementArrayBuffer::<u32>::new();
+    other.allocate(4, gl::DYNAMIC_DRAW);
+    assert_eq!(element_binding(), bound);
+    other.dynamic_draw_data(&[3, 4, 5]);
+    assert_eq!(element_binding(), bound);
+    other.update_range(3, &[6, 7]);
+    assert_eq!(element_binding(), bound);
+    let _stream = StreamElementArrayBuffer::<u32>::new(64);
+    assert_eq!(element_binding(), bound);
+    vao.unbind();
+}
+
+#[cfg(feature = "derive")]
+mod mesh {
+    use super::*;
+    use nalgebra_glm::Vec3;
+    use render_gl::{Mesh, Topology, Vertex};
+
+    #[derive(Clone, Copy, Vertex)]
+    #[repr(C)]
+    struct Position {
+        position: Vec3,
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn indexed_meshes_keep_their_index_buffer() {
+        let _gl = common::headless();
+        let vertices = [Position { position: Vec3::zeros() }; 3];
+        let mut mesh = Mesh::new_indexed(Topology::Triangles, &vertices, &[0_u32, 1, 2]).unwrap();
+        mesh.vertex_array().bind();
+        let bound = element_binding();
+        assert_ne!(bound, 0);
+        mesh.vertex_array().unbind();
+
+        mesh.update_indices(&[2, 1, 0, 0, 1, 2]).unwrap();
+        mesh.vertex_array().bind();
+        assert_eq!(element_binding(), bound);
+        mesh.vertex_array().unbind();
+    }
+}
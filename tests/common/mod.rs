@@ -0,0 +1,51 @@
+//! A headless GL context for the tests that need a GPU. Those tests are
+//! `#[ignore]`d, run them with `cargo test -- --ignored` on a machine with
+//! an EGL driver
+
+use std::ffi::CString;
+
+use glutin::api::egl::context::PossiblyCurrentContext;
+use glutin::api::egl::device::Device;
+use glutin::api::egl::display::Display;
+use glutin::config::{ConfigSurfaceTypes, ConfigTemplateBuilder};
+use glutin::context::{ContextApi, ContextAttributesBuilder, Version};
+use glutin::prelude::*;
+
+/// Keeps the context current on this thread while it's alive
+pub struct Headless {
+    _context: PossiblyCurrentContext,
+    _display: Display,
+}
+
+/// Makes a surfaceless GL 4.5 context current on the calling thread and
+/// loads the GL functions. Every test runs on its own thread, so each needs
+/// its own context
+pub fn headless() -> Headless {
+    let device = Device::query_devices()
+        .expect("no EGL device enumeration")
+        .next()
+        .expect("no EGL device");
+    let display = unsafe { Display::with_device(&device, None) }.expect("no EGL display");
+    let template = ConfigTemplateBuilder::new()
+        .with_surface_type(ConfigSurfaceTypes::empty())
+        .build();
+    let config = unsafe { display.find_configs(template) }
+        .expect("no EGL configs")
+        .next()
+        .expect("no surfaceless EGL config");
+    let attributes = ContextAttributesBuilder::new()
+        .with_context_api(ContextApi::OpenGl(Some(Version::new(4, 5))))
+        .build(None);
+    let context = unsafe { display.create_context(&config, &attributes) }
+        .expect("no GL 4.5 context")
+        .make_current_surfaceless()
+        .expect("couldn't make the context current");
+    gl::load_with(|name| {
+        let name = CString::new(name).unwrap();
+        display.get_proc_address(&name)
+    });
+    Headless {
+        _context: context,
+        _display: display,
+    }
+}
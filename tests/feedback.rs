@@ -0,0 +1,67 @@
+mod common;
+
+use render_gl::{ArrayBuffer, FrameBuffer, ProgramBuilder, U8RGBATexture, VertexArray};
+
+const VERTEX: &str = "#version 450 core
+out float doubled;
+void main() {
+    doubled = float(gl_VertexID) * 2.0;
+    gl_Position = vec4(0.0);
+}
+";
+
+/// Draws `count` points from `first`, capturing into the buffers bound
+fn capture(first: i32, count: i32) -> u64 {
+    let program = ProgramBuilder::new()
+        .vertex(VERTEX)
+        .transform_feedback(&["doubled"], gl::INTERLEAVED_ATTRIBS)
+        .build()
+        .unwrap();
+    // a surfaceless context has no default framebuffer to draw into
+    let framebuffer = FrameBuffer::new();
+    let mut color = U8RGBATexture::new(1, 1);
+    framebuffer.attach_texture(&mut color, gl::COLOR_ATTACHMENT0);
+    framebuffer.bind();
+    let vao = VertexArray::new();
+    vao.bind();
+    let feedback = program.begin_transform_feedback(gl::POINTS, true);
+    unsafe { gl::DrawArrays(gl::POINTS, first, count) };
+    let written = feedback.end();
+    vao.unbind();
+    framebuffer.unbind();
+    written.count()
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn captured_vertices_read_back() {
+    let _gl = common::headless();
+    let buffer = ArrayBuffer::<f32>::new();
+    buffer.allocate(4, gl::STREAM_READ);
+    buffer.bind_feedback(0);
+
+    assert_eq!(capture(0, 4), 4);
+    assert_eq!(buffer.read(), [0.0, 2.0, 4.0, 6.0]);
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn ranges_capture_into_part_of_the_buffer() {
+    let _gl = common::headless();
+    let buffer = ArrayBuffer::<f32>::new();
+    buffer.static_draw_data(&[-1.0; 4]);
+    buffer.bind_feedback_range(0, 1, 2);
+
+    assert_eq!(capture(5, 2), 2);
+    assert_eq!(buffer.read(), [-1.0, 10.0, 12.0, -1.0]);
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+#[should_panic(expected = "past the end of the buffer")]
+fn overflowing_ranges_are_rejected() {
+    let _gl = common::headless();
+    let buffer = ArrayBuffer::<f32>::new();
+    buffer.allocate(4, gl::STREAM_READ);
+    buffer.bind_feedback_range(0, 2, usize::MAX);
+}
@@ -0,0 +1,23 @@
+mod common;
+
+use render_gl::{FrameBuffer, RenderBuffer, U8RGBATexture};
+
+#[test]
+#[ignore = "needs a GPU"]
+fn color_texture_and_depth_stencil_render_buffer_are_complete() {
+    let _gl = common::headless();
+    let framebuffer = FrameBuffer::new();
+    let mut color = U8RGBATexture::new(64, 32);
+    let mut depth_stencil = RenderBuffer::new(gl::DEPTH24_STENCIL8, 64, 32);
+
+    framebuffer.attach_texture(&mut color, gl::COLOR_ATTACHMENT0);
+    framebuffer.attach_render_buffer(&mut depth_stencil, gl::DEPTH_STENCIL_ATTACHMENT);
+
+    // attaching doesn't leave the framebuffer bound
+    let mut bound: gl::types::GLint = -1;
+    unsafe { gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut bound) };
+    assert_eq!(bound, 0);
+    assert_eq!(color.attach_point, gl::COLOR_ATTACHMENT0);
+    assert_eq!(depth_stencil.attach_point, gl::DEPTH_STENCIL_ATTACHMENT);
+    assert!(framebuffer.status());
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use render_gl::{FrameBuffer, FrameContext, FullscreenPass, U8RGBATexture};
+
+const BODY: &str = "void mainImage(out vec4 color, in vec2 coord) { color = vec4(1.0, 0.0, 1.0, 1.0); }";
+
+fn bound(binding: gl::types::GLenum) -> gl::types::GLint {
+    let mut fbo: gl::types::GLint = -1;
+    unsafe { gl::GetIntegerv(binding, &mut fbo) };
+    fbo
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn render_to_leaves_both_framebuffer_bindings_alone() {
+    let _gl = common::headless();
+    let pass = FullscreenPass::new(BODY).unwrap();
+    let target = FrameBuffer::new();
+    let mut color = U8RGBATexture::new(4, 4);
+    target.attach_texture(&mut color, gl::COLOR_ATTACHMENT0);
+    let draw = FrameBuffer::new();
+    let read = FrameBuffer::new();
+    unsafe {
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, draw.fbo);
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, read.fbo);
+        gl::Viewport(0, 0, 4, 4);
+    }
+
+    pass.render_to(&target, &FrameContext::new(4, 4), &[]);
+
+    assert_eq!(bound(gl::DRAW_FRAMEBUFFER_BINDING), draw.fbo as i32);
+    assert_eq!(bound(gl::READ_FRAMEBUFFER_BINDING), read.fbo as i32);
+    assert!(color.get_pixel_data().iter().all(|&pixel| pixel == [255, 0, 255, 255]));
+}
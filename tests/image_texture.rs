@@ -0,0 +1,66 @@
+mod common;
+
+use std::io::Cursor;
+
+use image::{DynamicImage, GrayImage, ImageOutputFormat};
+use render_gl::{
+    FrameBuffer, FrameContext, FullscreenPass, ImageTexture, LoadOptions, U8RGBATexture,
+};
+
+/// A 2 by 2 gray PNG, 10 and 20 on the top row, 30 and 40 on the bottom
+fn gray_png() -> Vec<u8> {
+    let image = GrayImage::from_raw(2, 2, vec![10, 20, 30, 40]).unwrap();
+    let mut png = Cursor::new(Vec::new());
+    DynamicImage::ImageLuma8(image)
+        .write_to(&mut png, ImageOutputFormat::Png)
+        .unwrap();
+    png.into_inner()
+}
+
+const OPTIONS: LoadOptions = LoadOptions {
+    flip_vertically: true,
+    mipmaps: false,
+};
+
+#[test]
+#[ignore = "needs a GPU"]
+fn gray_png_decodes_bottom_row_first() {
+    let _gl = common::headless();
+    let texture = ImageTexture::from_bytes(&gray_png(), &OPTIONS).unwrap();
+    assert_eq!(texture.size(), (2, 2));
+    match texture {
+        ImageTexture::Gray(texture) => assert_eq!(texture.get_pixel_data(), [30, 40, 10, 20]),
+        _ => panic!("gray images load as gray textures"),
+    }
+
+    let unflipped = LoadOptions {
+        flip_vertically: false,
+        ..OPTIONS
+    };
+    match ImageTexture::from_bytes(&gray_png(), &unflipped).unwrap() {
+        ImageTexture::Gray(texture) => assert_eq!(texture.get_pixel_data(), [10, 20, 30, 40]),
+        _ => panic!("gray images load as gray textures"),
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn gray_textures_sample_as_gray() {
+    let _gl = common::headless();
+    let texture = ImageTexture::from_bytes(&gray_png(), &OPTIONS).unwrap();
+    let pass = FullscreenPass::new(
+        "void mainImage(out vec4 color, in vec2 coord) { color = texture(iChannel0, coord / iResolution.xy); }",
+    )
+    .unwrap();
+    let target = FrameBuffer::new();
+    let mut color = U8RGBATexture::new(2, 2);
+    target.attach_texture(&mut color, gl::COLOR_ATTACHMENT0);
+    unsafe { gl::Viewport(0, 0, 2, 2) };
+
+    pass.render_to(&target, &FrameContext::new(2, 2), &[&texture]);
+
+    assert_eq!(
+        color.get_pixel_data(),
+        [[30, 30, 30, 255], [40, 40, 40, 255], [10, 10, 10, 255], [20, 20, 20, 255]]
+    );
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use render_gl::{Material, Program, TextureBinding};
+
+const VERTEX: &str = "#version 450 core
+void main() { gl_Position = vec4(0.0); }
+";
+
+const FRAGMENT: &str = "#version 450 core
+uniform sampler2D albedo;
+uniform sampler2D normals;
+out vec4 color;
+void main() { color = texture(albedo, vec2(0.0)) + texture(normals, vec2(0.0)); }
+";
+
+/// Records the units it's attached to instead of binding anything
+#[derive(Default)]
+struct StubTexture {
+    units: RefCell<Vec<gl::types::GLuint>>,
+}
+
+impl TextureBinding for StubTexture {
+    fn attach_to_unit(&self, tex_unit: gl::types::GLuint) {
+        self.units.borrow_mut().push(tex_unit);
+    }
+}
+
+fn material() -> Material {
+    Material::new(Rc::new(Program::from_src(VERTEX, FRAGMENT).unwrap()))
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn texture_units_are_given_in_order() {
+    let _gl = common::headless();
+    let albedo = Rc::new(StubTexture::default());
+    let normals = Rc::new(StubTexture::default());
+    let mut material = material();
+    material
+        .set_texture("albedo", albedo.clone())
+        .set_texture("normals", normals.clone());
+
+    assert_eq!(material.texture_unit("albedo"), Some(0));
+    assert_eq!(material.texture_unit("normals"), Some(1));
+    assert_eq!(material.texture_unit("missing"), None);
+
+    material.apply();
+    assert_eq!(*albedo.units.borrow(), [0]);
+    assert_eq!(*normals.units.borrow(), [1]);
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn replacing_a_texture_keeps_its_unit() {
+    let _gl = common::headless();
+    let first = Rc::new(StubTexture::default());
+    let second = Rc::new(StubTexture::default());
+    let replacement = Rc::new(StubTexture::default());
+    let mut material = material();
+    material
+        .set_texture("albedo", first.clone())
+        .set_texture("normals", second.clone())
+        .set_texture("albedo", replacement.clone());
+
+    assert_eq!(material.texture_unit("albedo"), Some(0));
+    assert_eq!(material.texture_unit("normals"), Some(1));
+
+    material.apply();
+    assert!(first.units.borrow().is_empty());
+    assert_eq!(*replacement.units.borrow(), [0]);
+    assert_eq!(*second.units.borrow(), [1]);
+}
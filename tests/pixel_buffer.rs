@@ -0,0 +1,73 @@
+mod common;
+
+use std::time::Duration;
+
+use render_gl::{
+    FrameBuffer, TextureTypeU8RGB, TextureTypeU8RGBA, U8RGBATexture, U8RGBTexture,
+};
+
+#[test]
+#[ignore = "needs a GPU"]
+fn empty_reads_give_empty_data() {
+    let _gl = common::headless();
+    let framebuffer = FrameBuffer::new();
+    let mut color = U8RGBATexture::new(1, 1);
+    framebuffer.attach_texture(&mut color, gl::COLOR_ATTACHMENT0);
+    let read = framebuffer.read_async::<TextureTypeU8RGBA, [u8; 4]>(gl::COLOR_ATTACHMENT0, 0, 0, 0, 0);
+    assert_eq!(read.into_data(), Ok(Vec::new()));
+}
+
+// 3 pixels of 3 bytes make rows that aren't a multiple of 4 bytes
+const RGB: [[u8; 3]; 6] = [
+    [0, 1, 2], [3, 4, 5], [6, 7, 8],
+    [9, 10, 11], [12, 13, 14], [255, 254, 253],
+];
+
+const RGBA: [[u8; 4]; 6] = [
+    [0, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11],
+    [12, 13, 14, 15], [16, 17, 18, 19], [255, 254, 253, 252],
+];
+
+fn pack_alignment() -> gl::types::GLint {
+    let mut alignment: gl::types::GLint = 0;
+    unsafe { gl::GetIntegerv(gl::PACK_ALIGNMENT, &mut alignment) };
+    alignment
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn texture_reads_round_trip() {
+    let _gl = common::headless();
+    let rgb = U8RGBTexture::new_from_data(&RGB, 3, 2);
+    let read = rgb.read_async();
+    assert_eq!((read.width(), read.height()), (3, 2));
+    assert!(read.wait(Duration::from_secs(5)));
+    assert_eq!(read.into_data().unwrap(), RGB);
+
+    let rgba = U8RGBATexture::new_from_data(&RGBA, 3, 2);
+    assert_eq!(rgba.read_async().into_data().unwrap(), RGBA);
+    // the caller's alignment is put back
+    assert_eq!(pack_alignment(), 4);
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn framebuffer_reads_round_trip() {
+    let _gl = common::headless();
+    let framebuffer = FrameBuffer::new();
+    let mut color = U8RGBATexture::new_from_data(&RGBA, 3, 2);
+    framebuffer.attach_texture(&mut color, gl::COLOR_ATTACHMENT0);
+
+    let read = framebuffer.read_async::<TextureTypeU8RGBA, [u8; 4]>(gl::COLOR_ATTACHMENT0, 0, 0, 3, 2);
+    assert_eq!(read.into_data().unwrap(), RGBA);
+
+    // the top row only
+    let read = framebuffer.read_async::<TextureTypeU8RGBA, [u8; 4]>(gl::COLOR_ATTACHMENT0, 0, 1, 3, 1);
+    assert_eq!(read.into_data().unwrap(), RGBA[3..]);
+
+    // read as RGB, odd rows again
+    let read = framebuffer.read_async::<TextureTypeU8RGB, [u8; 3]>(gl::COLOR_ATTACHMENT0, 0, 0, 3, 2);
+    let rgb: Vec<[u8; 3]> = RGBA.iter().map(|p| [p[0], p[1], p[2]]).collect();
+    assert_eq!(read.into_data().unwrap(), rgb);
+    assert_eq!(pack_alignment(), 4);
+}
@@ -0,0 +1,15 @@
+mod common;
+
+use render_gl::{Sampler, SamplerDesc};
+
+#[test]
+#[ignore = "needs a GPU"]
+fn checking_anisotropy_support_keeps_pending_errors() {
+    let _gl = common::headless();
+    unsafe {
+        gl::Enable(0xFFFF);
+    }
+    let _sampler = Sampler::new(&SamplerDesc::default().anisotropy(4.0));
+    assert_eq!(unsafe { gl::GetError() }, gl::INVALID_ENUM);
+    assert_eq!(unsafe { gl::GetError() }, gl::NO_ERROR);
+}
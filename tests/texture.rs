@@ -0,0 +1,86 @@
+mod common;
+
+use render_gl::*;
+
+/// Uploads a 3 by 2 texture, reads it back and compares. Three pixels per
+/// row catches unpadded rows of 1, 2 and 3 byte pixels
+fn round_trip<TTex, TData>(data: [TData; 6])
+where
+    TTex: TextureType<TData>,
+    TData: Default + Clone + PartialEq + std::fmt::Debug,
+{
+    let texture = Texture::<TTex, TData>::new_from_data(&data, 3, 2);
+    assert_eq!(texture.get_pixel_data(), data);
+    assert_eq!(unsafe { gl::GetError() }, gl::NO_ERROR);
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn normalized_textures_round_trip() {
+    let _gl = common::headless();
+    round_trip::<TextureTypeRed, _>([0, 1, 2, 3, 128, 255]);
+    round_trip::<TextureTypeU8RG, _>([[0, 1], [2, 3], [4, 5], [6, 7], [8, 9], [255, 254]]);
+    round_trip::<TextureTypeU8RGB, _>([
+        [0, 1, 2], [3, 4, 5], [6, 7, 8], [9, 10, 11], [12, 13, 14], [255, 254, 253],
+    ]);
+    round_trip::<TextureTypeU8RGBA, _>([
+        [0, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11],
+        [12, 13, 14, 15], [16, 17, 18, 19], [255, 254, 253, 252],
+    ]);
+    round_trip::<TextureTypeR16, _>([0, 1, 2, 3, 32768, 65535]);
+    round_trip::<TextureTypeU16RGB, _>([
+        [0, 1, 2], [3, 4, 5], [6, 7, 8], [9, 10, 11], [12, 13, 14], [65535, 65534, 65533],
+    ]);
+    round_trip::<TextureTypeU16RGBA, _>([
+        [0, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11],
+        [12, 13, 14, 15], [16, 17, 18, 19], [65535, 65534, 65533, 65532],
+    ]);
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn srgb_textures_round_trip() {
+    let _gl = common::headless();
+    round_trip::<TextureTypeSRGB, _>([
+        [0, 1, 2], [3, 4, 5], [6, 7, 8], [9, 10, 11], [12, 13, 14], [255, 254, 253],
+    ]);
+    round_trip::<TextureTypeSRGBA, _>([
+        [0, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11],
+        [12, 13, 14, 15], [16, 17, 18, 19], [255, 254, 253, 252],
+    ]);
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn float_textures_round_trip() {
+    let _gl = common::headless();
+    round_trip::<TextureTypeF32, _>([0.0, -1.5, 2.25, 1e6, -1e-6, 0.1]);
+    round_trip::<TextureTypeF32RG, _>([
+        [0.0, 1.0], [-1.5, 2.25], [1e6, -1e-6], [0.1, 0.2], [3.0, 4.0], [-5.0, 6.5],
+    ]);
+    round_trip::<TextureTypeRGB, _>([
+        [0.0, 1.0, 2.0], [-1.5, 2.25, 0.1], [1e6, -1e-6, 3.0],
+        [4.0, 5.0, 6.0], [7.0, 8.0, 9.0], [-10.0, 11.5, 12.25],
+    ]);
+    // values half floats hold exactly
+    round_trip::<TextureTypeRGBA16F, _>([
+        [0.0, 1.0, 2.0, 0.5], [-1.5, 2.25, 0.125, 4.0], [8.0, -0.25, 3.0, 1.0],
+        [4.0, 5.0, 6.0, 7.0], [7.0, 8.0, 9.0, 10.0], [-10.0, 11.5, 12.25, 1024.0],
+    ]);
+    round_trip::<TextureTypeRGBA32F, _>([
+        [0.0, 1.0, 2.0, 0.5], [-1.5, 2.25, 0.1, 4.0], [1e6, -1e-6, 3.0, 1.0],
+        [4.0, 5.0, 6.0, 7.0], [7.0, 8.0, 9.0, 10.0], [-10.0, 11.5, 12.25, 1e-3],
+    ]);
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn integer_textures_round_trip() {
+    let _gl = common::headless();
+    round_trip::<TextureTypeI32, _>([0, -1, 2, i32::MIN, i32::MAX, 1 << 24]);
+    round_trip::<TextureTypeU32, _>([0, 1, 2, u32::MAX, 1 << 31, 1 << 24]);
+    round_trip::<TextureTypeU32RGBA, _>([
+        [0, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11],
+        [12, 13, 14, 15], [16, 17, 18, 19], [u32::MAX, 1 << 31, 1 << 24, 65536],
+    ]);
+}
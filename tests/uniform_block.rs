@@ -0,0 +1,32 @@
+mod common;
+
+use nalgebra_glm::Vec3;
+use render_gl::{Program, UniformBlock};
+
+const VERTEX: &str = "#version 450 core
+layout(std140) uniform B { vec3 v; };
+void main() { gl_Position = vec4(v, 1.0); }
+";
+
+const FRAGMENT: &str = "#version 450 core
+out vec4 color;
+void main() { color = vec4(1.0); }
+";
+
+#[test]
+#[ignore = "needs a GPU"]
+fn vec3_block_gets_a_whole_block_of_storage() {
+    let _gl = common::headless();
+    let program = Program::from_src(VERTEX, FRAGMENT).unwrap();
+    let block = UniformBlock::new(3, &Vec3::new(1.0, 2.0, 3.0));
+    assert_eq!(program.uniform_block_data_size("B"), Some(16));
+    assert_eq!(block.attach(&program, "B"), Ok(()));
+
+    let mut buffer: gl::types::GLint = 0;
+    let mut size: gl::types::GLint = 0;
+    unsafe {
+        gl::GetIntegeri_v(gl::UNIFORM_BUFFER_BINDING, 3, &mut buffer);
+        gl::GetNamedBufferParameteriv(buffer as u32, gl::BUFFER_SIZE, &mut size);
+    }
+    assert!(size >= 16, "buffer is {} bytes", size);
+}